@@ -0,0 +1,40 @@
+//! Prints a calendar-style coverage map per symbol.
+//!
+//! cargo run --example coverage -- <dataset> <schema> <symbol>...
+//! e.g. cargo run --example coverage -- futures mbp-1 ES.n.0 NQ.n.0
+use mbinary::enums::{Dataset, Schema};
+use midas_client::historical::Historical;
+use std::str::FromStr;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    let base_url = std::env::var("MIDAS_URL").expect("Expected MIDAS_URL.");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 3 {
+        anyhow::bail!("Usage: coverage <dataset> <schema> <symbol>...");
+    }
+    let dataset = Dataset::from_str(&args[0])?;
+    let schema = Schema::from_str(&args[1])?;
+    let symbols = args[2..].to_vec();
+
+    let client = Historical::new(&base_url);
+    let response = client.coverage(&dataset, &symbols, &schema).await?;
+
+    if response.status != "success" {
+        anyhow::bail!("Coverage request failed: {}", response.message);
+    }
+
+    for coverage in response.data {
+        println!(
+            "{} {} -> {}",
+            coverage.symbol,
+            coverage.first_day(),
+            coverage.last_day()
+        );
+        println!("{}", coverage.calendar());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Exports the trades or signals of a backtest or live session, with tickers joined from the
+//! instruments of `dataset`. The format follows the extension of `path`, `.parquet` needs
+//! `--features parquet`.
+//!
+//! cargo run --example export -- <backtest|live> <id> <trades|signals> <dataset> <path>
+//! e.g. cargo run --example export -- backtest 12 trades equities trades.csv
+use mbinary::backtest::{Signals, Trades};
+use mbinary::enums::Dataset;
+use midas_client::cache::InstrumentCache;
+use midas_client::export::{export_signals, export_trades, PRICE_SCALE};
+use midas_client::instrument::Instruments;
+use midas_client::trading::Trading;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    let base_url = std::env::var("MIDAS_URL").expect("Expected MIDAS_URL.");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 5 {
+        anyhow::bail!("Usage: export <backtest|live> <id> <trades|signals> <dataset> <path>");
+    }
+    let id: i32 = args[1].parse()?;
+    let dataset = Dataset::from_str(&args[3])?;
+    let path = &args[4];
+
+    let client = Trading::new(&base_url);
+    let (trades, signals): (Vec<Trades>, Vec<Signals>) = match args[0].as_str() {
+        "backtest" => match client.get_backtest(&id).await?.data {
+            Some(backtest) => (backtest.trades, backtest.signals),
+            None => anyhow::bail!("Backtest {} not found.", id),
+        },
+        "live" => match client.get_live(&id).await?.data.into_iter().next() {
+            Some(live) => (live.trades, live.signals),
+            None => anyhow::bail!("Live session {} not found.", id),
+        },
+        other => anyhow::bail!("Unknown source '{}', expected backtest or live.", other),
+    };
+
+    let mut instruments = InstrumentCache::new(
+        Instruments::new(&base_url),
+        dataset,
+        Duration::from_secs(60),
+    );
+    instruments.refresh().await?;
+
+    match args[2].as_str() {
+        "trades" => export_trades(&trades, path, PRICE_SCALE, Some(&instruments))?,
+        "signals" => export_signals(&signals, path, PRICE_SCALE, Some(&instruments))?,
+        other => anyhow::bail!("Unknown rows '{}', expected trades or signals.", other),
+    }
+
+    Ok(())
+}
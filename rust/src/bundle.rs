@@ -0,0 +1,355 @@
+use crate::error::{Error, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use mbinary::backtest::BacktestData;
+use mbinary::decode::Decoder;
+use mbinary::encode::CombinedEncoder;
+use mbinary::live::LiveData;
+use mbinary::metadata::Metadata;
+use mbinary::record_enum::RecordEnum;
+use mbinary::record_ref::RecordRef;
+use mbinary::records::RecordHeader;
+use mbinary::symbols::SymbolMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const BACKTEST: &str = "backtest.json";
+const LIVE: &str = "live.json";
+const PARAMETERS: &str = "parameters.json";
+const MARKET_DATA: &str = "market_data.bin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleKind {
+    Backtest,
+    Live,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub kind: BundleKind,
+    /// Backtest name or live strategy name.
+    pub name: String,
+    pub has_market_data: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum BundleData {
+    Backtest(BacktestData),
+    Live(LiveData),
+}
+
+/// Backtest or live session moved between servers as a single `.tar.gz` archive, with the
+/// MBN market data it ran on when included.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    pub data: BundleData,
+    pub market_data: Option<Vec<u8>>,
+}
+
+impl Bundle {
+    pub fn backtest(backtest: BacktestData, market_data: Option<Vec<u8>>) -> Self {
+        Bundle {
+            manifest: BundleManifest {
+                version: BUNDLE_VERSION,
+                kind: BundleKind::Backtest,
+                name: backtest.metadata.backtest_name.clone(),
+                has_market_data: market_data.is_some(),
+            },
+            data: BundleData::Backtest(backtest),
+            market_data,
+        }
+    }
+
+    pub fn live(live: LiveData, market_data: Option<Vec<u8>>) -> Self {
+        Bundle {
+            manifest: BundleManifest {
+                version: BUNDLE_VERSION,
+                kind: BundleKind::Live,
+                name: live.parameters.strategy_name.clone(),
+                has_market_data: market_data.is_some(),
+            },
+            data: BundleData::Live(live),
+            market_data,
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+
+        append(
+            &mut archive,
+            MANIFEST,
+            &serde_json::to_vec_pretty(&self.manifest)?,
+        )?;
+
+        match &self.data {
+            BundleData::Backtest(backtest) => {
+                append(&mut archive, BACKTEST, &serde_json::to_vec(backtest)?)?;
+                append(
+                    &mut archive,
+                    PARAMETERS,
+                    &serde_json::to_vec_pretty(&backtest.metadata.parameters)?,
+                )?;
+            }
+            BundleData::Live(live) => {
+                append(&mut archive, LIVE, &serde_json::to_vec(live)?)?;
+                append(
+                    &mut archive,
+                    PARAMETERS,
+                    &serde_json::to_vec_pretty(&live.parameters)?,
+                )?;
+            }
+        }
+
+        if let Some(market_data) = &self.market_data {
+            append(&mut archive, MARKET_DATA, market_data)?;
+        }
+
+        archive.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+        let mut entries: HashMap<String, Vec<u8>> = HashMap::new();
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            entries.insert(name, bytes);
+        }
+
+        let manifest: BundleManifest = serde_json::from_slice(read_entry(&entries, MANIFEST)?)?;
+
+        if manifest.version > BUNDLE_VERSION {
+            return Err(Error::CustomError(format!(
+                "Unsupported bundle version {}, expected {} or lower.",
+                manifest.version, BUNDLE_VERSION
+            )));
+        }
+
+        let data = match manifest.kind {
+            BundleKind::Backtest => {
+                BundleData::Backtest(serde_json::from_slice(read_entry(&entries, BACKTEST)?)?)
+            }
+            BundleKind::Live => {
+                BundleData::Live(serde_json::from_slice(read_entry(&entries, LIVE)?)?)
+            }
+        };
+
+        Ok(Bundle {
+            manifest,
+            data,
+            market_data: entries.remove(MARKET_DATA),
+        })
+    }
+}
+
+/// Metadata of MBN market data, its mappings hold the instrument ids of the server it was
+/// exported from.
+pub fn market_data_metadata(market_data: &[u8]) -> Result<Metadata> {
+    Decoder::new(Cursor::new(market_data))?
+        .metadata()
+        .ok_or_else(|| Error::CustomError("Bundled market data has no metadata.".to_string()))
+}
+
+/// Rewrites the instrument ids of MBN market data to the ids of the same tickers in `ids`, e.g.
+/// as found on the server the bundle is imported into. Refuses records of an id missing from
+/// the mappings and tickers missing from `ids`.
+pub fn remap_market_data(market_data: &[u8], ids: &HashMap<String, u32>) -> Result<Vec<u8>> {
+    let mut decoder = Decoder::new(Cursor::new(market_data))?;
+    let mut metadata = decoder
+        .metadata()
+        .ok_or_else(|| Error::CustomError("Bundled market data has no metadata.".to_string()))?;
+    let mut records = decoder.decode()?;
+
+    let mut remapped: HashMap<u32, u32> = HashMap::new();
+    let mut mappings = SymbolMap::new();
+    for (id, ticker) in metadata.mappings.map.iter() {
+        let target = ids.get(ticker).ok_or_else(|| {
+            Error::CustomError(format!(
+                "Instrument {} of the bundled market data was not found on the target server.",
+                ticker
+            ))
+        })?;
+        remapped.insert(*id, *target);
+        mappings.add_instrument(ticker, *target);
+    }
+    metadata.mappings = mappings;
+
+    for record in records.iter_mut() {
+        let header = header_mut(record);
+        header.instrument_id = *remapped.get(&header.instrument_id).ok_or_else(|| {
+            Error::CustomError(format!(
+                "Bundled market data has records of unmapped instrument id {}.",
+                header.instrument_id
+            ))
+        })?;
+    }
+
+    let refs: Vec<RecordRef> = records
+        .iter()
+        .map(|record| record.to_record_ref())
+        .collect();
+    let mut bytes = Vec::new();
+    CombinedEncoder::new(&mut bytes).encode(&metadata, &refs)?;
+    Ok(bytes)
+}
+
+fn header_mut(record: &mut RecordEnum) -> &mut RecordHeader {
+    match record {
+        RecordEnum::Mbp1(msg) => &mut msg.hd,
+        RecordEnum::Ohlcv(msg) => &mut msg.hd,
+        RecordEnum::Trade(msg) => &mut msg.hd,
+        RecordEnum::Tbbo(msg) => &mut msg.hd,
+        RecordEnum::Bbo(msg) => &mut msg.hd,
+    }
+}
+
+fn append<W: Write>(archive: &mut tar::Builder<W>, name: &str, bytes: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+fn read_entry<'a>(entries: &'a HashMap<String, Vec<u8>>, name: &str) -> Result<&'a [u8]> {
+    entries
+        .get(name)
+        .map(|bytes| bytes.as_slice())
+        .ok_or_else(|| Error::CustomError(format!("Bundle is missing {}.", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mbinary::enums::{Dataset, Schema};
+    use mbinary::records::{BidAskPair, Mbp1Msg};
+    use std::fs;
+
+    fn dummy_market_data(ticker: &str, id: u32) -> Result<Vec<u8>> {
+        let mbp = Mbp1Msg {
+            hd: RecordHeader::new::<Mbp1Msg>(id, 1704209103644092564, 0),
+            price: 6770,
+            size: 1,
+            action: 1,
+            side: 2,
+            depth: 0,
+            flags: 0,
+            ts_recv: 1704209103644092564,
+            ts_in_delta: 17493,
+            sequence: 739763,
+            discriminator: 0,
+            levels: [BidAskPair {
+                ask_px: 1,
+                bid_px: 1,
+                bid_sz: 2,
+                ask_sz: 2,
+                bid_ct: 10,
+                ask_ct: 20,
+            }],
+        };
+        let mut mappings = SymbolMap::new();
+        mappings.add_instrument(ticker, id);
+        let metadata = Metadata::new(
+            Schema::Mbp1,
+            Dataset::Equities,
+            1704209103644092564,
+            1704209103644092566,
+            mappings,
+        );
+
+        let mut bytes = Vec::new();
+        CombinedEncoder::new(&mut bytes).encode(&metadata, &[(&mbp).into()])?;
+        Ok(bytes)
+    }
+
+    #[test]
+    fn test_remap_market_data() -> Result<()> {
+        let market_data = dummy_market_data("AAPL", 7)?;
+        let ids = HashMap::from([("AAPL".to_string(), 42)]);
+
+        // Test
+        let remapped = remap_market_data(&market_data, &ids)?;
+        let missing = remap_market_data(&market_data, &HashMap::new());
+
+        // Validate
+        let mut decoder = Decoder::new(Cursor::new(remapped.as_slice()))?;
+        let metadata = decoder.metadata().unwrap();
+        let records = decoder.decode()?;
+        assert_eq!(
+            metadata.mappings.get_instrument_ticker(42),
+            Some("AAPL".to_string())
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].msg().header().instrument_id, 42);
+        assert_eq!(market_data_metadata(&market_data)?.mappings.map.len(), 1);
+        assert!(matches!(missing, Err(Error::CustomError(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() -> Result<()> {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+        let path = "tests/test_bundle.tar.gz";
+
+        // Test
+        Bundle::backtest(backtest.clone(), Some(vec![1, 2, 3])).write(path)?;
+        let bundle = Bundle::read(path)?;
+
+        // Validate
+        assert_eq!(bundle.manifest.kind, BundleKind::Backtest);
+        assert_eq!(bundle.manifest.name, backtest.metadata.backtest_name);
+        assert_eq!(bundle.market_data, Some(vec![1, 2, 3]));
+        match bundle.data {
+            BundleData::Backtest(data) => assert_eq!(data.trades.len(), backtest.trades.len()),
+            BundleData::Live(_) => panic!("Expected a backtest bundle"),
+        }
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_live_bundle_without_market_data() -> Result<()> {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        let live: LiveData = serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+        let path = "tests/test_live_bundle.tar.gz";
+
+        // Test
+        Bundle::live(live, None).write(path)?;
+        let bundle = Bundle::read(path)?;
+
+        // Validate
+        assert_eq!(bundle.manifest.kind, BundleKind::Live);
+        assert!(!bundle.manifest.has_market_data);
+        assert!(bundle.market_data.is_none());
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::{Error, Result};
+use crate::instrument::Instruments;
+use mbinary::enums::Dataset;
+use mbinary::symbols::Instrument;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// In-memory map of the instruments in a dataset, loaded on first use and reloaded once
+/// older than the TTL.
+pub struct InstrumentCache {
+    client: Instruments,
+    dataset: Dataset,
+    ttl: Duration,
+    loaded_at: Option<Instant>,
+    by_id: HashMap<u32, Instrument>,
+    by_ticker: HashMap<String, u32>,
+}
+
+impl InstrumentCache {
+    pub fn new(client: Instruments, dataset: Dataset, ttl: Duration) -> Self {
+        InstrumentCache {
+            client,
+            dataset,
+            ttl,
+            loaded_at: None,
+            by_id: HashMap::new(),
+            by_ticker: HashMap::new(),
+        }
+    }
+
+    /// Reloads the instruments from the server.
+    pub async fn refresh(&mut self) -> Result<()> {
+        let response = self.client.list_dataset_symbols(&self.dataset).await?;
+
+        if response.status != "success" {
+            return Err(Error::CustomError(response.message));
+        }
+
+        self.load(response.data);
+        Ok(())
+    }
+
+    /// Replaces the cached instruments, resetting the TTL.
+    pub fn load(&mut self, instruments: Vec<Instrument>) {
+        self.by_id.clear();
+        self.by_ticker.clear();
+
+        for instrument in instruments {
+            if let Some(id) = instrument.instrument_id {
+                self.by_ticker.insert(instrument.ticker.clone(), id);
+                self.by_id.insert(id, instrument);
+            }
+        }
+
+        self.loaded_at = Some(Instant::now());
+    }
+
+    pub fn is_stale(&self) -> bool {
+        match self.loaded_at {
+            Some(loaded_at) => loaded_at.elapsed() >= self.ttl,
+            None => true,
+        }
+    }
+
+    async fn ensure_fresh(&mut self) -> Result<()> {
+        if self.is_stale() {
+            self.refresh().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get(&mut self, id: u32) -> Result<Option<&Instrument>> {
+        self.ensure_fresh().await?;
+        Ok(self.by_id.get(&id))
+    }
+
+    pub async fn ticker(&mut self, id: u32) -> Result<Option<&str>> {
+        self.ensure_fresh().await?;
+        Ok(self.cached_ticker(id))
+    }
+
+    pub async fn id(&mut self, ticker: &str) -> Result<Option<u32>> {
+        self.ensure_fresh().await?;
+        Ok(self.cached_id(ticker))
+    }
+
+    /// Looks up the ticker without loading or refreshing, e.g. while labelling records.
+    pub fn cached_ticker(&self, id: u32) -> Option<&str> {
+        self.by_id
+            .get(&id)
+            .map(|instrument| instrument.ticker.as_str())
+    }
+
+    /// Looks up the id without loading or refreshing.
+    pub fn cached_id(&self, ticker: &str) -> Option<u32> {
+        self.by_ticker.get(ticker).copied()
+    }
+
+    /// Looks up the instrument by ticker without loading or refreshing.
+    pub fn cached_instrument(&self, ticker: &str) -> Option<&Instrument> {
+        self.cached_id(ticker).and_then(|id| self.by_id.get(&id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mbinary::vendors::Vendors;
+
+    fn dummy_instrument(id: u32, ticker: &str) -> Instrument {
+        Instrument::new(
+            Some(id),
+            ticker,
+            "Lean hogs",
+            Dataset::Futures,
+            Vendors::Databento,
+            1,
+            1,
+            1,
+            1,
+            false,
+            true,
+        )
+    }
+
+    #[test]
+    fn test_cache_lookup() {
+        let client = Instruments::new("http://127.0.0.1:8080");
+        let mut cache = InstrumentCache::new(client, Dataset::Futures, Duration::from_secs(60));
+        assert!(cache.is_stale());
+
+        // Test
+        cache.load(vec![
+            dummy_instrument(1, "HEG4"),
+            dummy_instrument(2, "HEJ4"),
+        ]);
+
+        // Validate
+        assert!(!cache.is_stale());
+        assert_eq!(cache.cached_ticker(2), Some("HEJ4"));
+        assert_eq!(cache.cached_id("HEG4"), Some(1));
+        assert_eq!(cache.cached_id("HEK4"), None);
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        let client = Instruments::new("http://127.0.0.1:8080");
+        let mut cache = InstrumentCache::new(client, Dataset::Futures, Duration::ZERO);
+
+        // Test
+        cache.load(vec![dummy_instrument(1, "HEG4")]);
+
+        // Validate
+        assert!(cache.is_stale());
+    }
+}
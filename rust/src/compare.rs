@@ -0,0 +1,423 @@
+use crate::error::Result;
+use crate::report::{markdown_escape, ReportConfig};
+use crate::stats::display_scale;
+use mbinary::backtest::{BacktestData, TimeseriesStats, Trades};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parameter with a different value in at least one backtest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterDiff {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Static stat across backtests, `deltas` are relative to the first backtest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatDelta {
+    pub name: String,
+    pub values: Vec<f64>,
+    pub deltas: Vec<f64>,
+}
+
+/// Equity of each backtest at a timestamp, `None` where a backtest has no value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub values: Vec<Option<i64>>,
+}
+
+/// Trades of a backtest missing from the first backtest and the other way around.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeDiff {
+    pub backtest_name: String,
+    pub only_in_baseline: Vec<Trades>,
+    pub only_in_other: Vec<Trades>,
+}
+
+/// Structured diff of two or more backtests, the first one is the baseline.
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub backtest_names: Vec<String>,
+    pub parameters: Vec<ParameterDiff>,
+    pub stats: Vec<StatDelta>,
+    pub equity: Vec<EquityPoint>,
+    pub trades: Vec<TradeDiff>,
+}
+
+pub fn compare(backtests: &[BacktestData]) -> Result<Comparison> {
+    let backtest_names = backtests
+        .iter()
+        .map(|backtest| backtest.metadata.backtest_name.clone())
+        .collect();
+
+    let parameters = backtests
+        .iter()
+        .map(|backtest| serde_json::to_value(&backtest.metadata.parameters))
+        .collect::<std::result::Result<Vec<Value>, _>>()?;
+    let static_stats = backtests
+        .iter()
+        .map(|backtest| serde_json::to_value(&backtest.metadata.static_stats))
+        .collect::<std::result::Result<Vec<Value>, _>>()?;
+
+    Ok(Comparison {
+        backtest_names,
+        parameters: parameter_diffs(&parameters),
+        stats: stat_deltas(&static_stats),
+        equity: align_equity(backtests),
+        trades: trade_diffs(backtests),
+    })
+}
+
+fn field_names(values: &[Value]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for value in values {
+        if let Value::Object(map) = value {
+            for key in map.keys() {
+                if !names.contains(key) {
+                    names.push(key.clone());
+                }
+            }
+        }
+    }
+    names
+}
+
+fn parameter_diffs(parameters: &[Value]) -> Vec<ParameterDiff> {
+    field_names(parameters)
+        .into_iter()
+        .filter_map(|name| {
+            let values: Vec<String> = parameters
+                .iter()
+                .map(|value| match value.get(&name) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                })
+                .collect();
+
+            if values.iter().all(|value| *value == values[0]) {
+                None
+            } else {
+                Some(ParameterDiff { name, values })
+            }
+        })
+        .collect()
+}
+
+fn stat_deltas(static_stats: &[Value]) -> Vec<StatDelta> {
+    field_names(static_stats)
+        .into_iter()
+        .map(|name| {
+            let values: Vec<f64> = static_stats
+                .iter()
+                .map(|value| value.get(&name).and_then(Value::as_f64).unwrap_or(0.0))
+                .collect();
+            let baseline = values.first().copied().unwrap_or(0.0);
+            let deltas = values.iter().map(|value| value - baseline).collect();
+
+            StatDelta {
+                name,
+                values,
+                deltas,
+            }
+        })
+        .collect()
+}
+
+/// Merges the timeseries of all backtests in timestamp order, advancing one cursor per
+/// backtest.
+fn align_equity(backtests: &[BacktestData]) -> Vec<EquityPoint> {
+    let series: Vec<Vec<&TimeseriesStats>> = backtests
+        .iter()
+        .map(|backtest| {
+            let mut stats: Vec<&TimeseriesStats> =
+                backtest.period_timeseries_stats.iter().collect();
+            stats.sort_by_key(|stat| stat.timestamp);
+            stats
+        })
+        .collect();
+    let mut cursors = vec![0; series.len()];
+    let mut points = Vec::new();
+
+    loop {
+        let next = series
+            .iter()
+            .zip(cursors.iter())
+            .filter_map(|(stats, cursor)| stats.get(*cursor))
+            .map(|stat| stat.timestamp)
+            .min();
+        let timestamp = match next {
+            Some(timestamp) => timestamp,
+            None => break,
+        };
+
+        let values = series
+            .iter()
+            .zip(cursors.iter_mut())
+            .map(|(stats, cursor)| match stats.get(*cursor) {
+                Some(stat) if stat.timestamp == timestamp => {
+                    // Keep the first value of repeated timestamps
+                    while stats.get(*cursor).map(|s| s.timestamp) == Some(timestamp) {
+                        *cursor += 1;
+                    }
+                    Some(stat.equity_value)
+                }
+                _ => None,
+            })
+            .collect();
+
+        points.push(EquityPoint { timestamp, values });
+    }
+
+    points
+}
+
+fn trade_key(trade: &Trades) -> String {
+    format!(
+        "{}|{}|{}|{}|{}",
+        trade.timestamp, trade.ticker, trade.action, trade.quantity, trade.avg_price
+    )
+}
+
+fn trade_counts(trades: &[Trades]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for trade in trades {
+        *counts.entry(trade_key(trade)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Trades of `trades` left over once each is matched against one trade with the same key in
+/// `counts`, so repeated identical trades are compared by count.
+fn unmatched(trades: &[Trades], mut counts: HashMap<String, usize>) -> Vec<Trades> {
+    trades
+        .iter()
+        .filter(|trade| match counts.get_mut(&trade_key(trade)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn trade_diffs(backtests: &[BacktestData]) -> Vec<TradeDiff> {
+    let baseline = match backtests.first() {
+        Some(baseline) => baseline,
+        None => return vec![],
+    };
+    let baseline_counts = trade_counts(&baseline.trades);
+
+    backtests
+        .iter()
+        .skip(1)
+        .map(|other| TradeDiff {
+            backtest_name: other.metadata.backtest_name.clone(),
+            only_in_baseline: unmatched(&baseline.trades, trade_counts(&other.trades)),
+            only_in_other: unmatched(&other.trades, baseline_counts.clone()),
+        })
+        .collect()
+}
+
+impl Comparison {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Renders the comparison as Markdown, with the static stats scaled as in the report.
+    pub fn to_markdown(&self, config: &ReportConfig) -> String {
+        let mut output = String::from("# Backtest comparison\n\n");
+        let names: Vec<String> = self
+            .backtest_names
+            .iter()
+            .map(|name| markdown_escape(name))
+            .collect();
+        let header = format!("| | {} |\n", names.join(" | "));
+        let divider = format!("|---|{}\n", "---|".repeat(names.len()));
+
+        output.push_str("## Parameters\n\n");
+        if self.parameters.is_empty() {
+            output.push_str("No parameter changes.\n\n");
+        } else {
+            output.push_str(&header);
+            output.push_str(&divider);
+            for diff in &self.parameters {
+                let values: Vec<String> = diff
+                    .values
+                    .iter()
+                    .map(|value| markdown_escape(value))
+                    .collect();
+                output.push_str(&format!(
+                    "| {} | {} |\n",
+                    markdown_escape(&diff.name),
+                    values.join(" | ")
+                ));
+            }
+            output.push('\n');
+        }
+
+        output.push_str("## Static stats\n\n");
+        output.push_str(&header);
+        output.push_str(&divider);
+        for stat in &self.stats {
+            let scale = display_scale(&stat.name, &config.stats, config.price_scale);
+            let cells: Vec<String> = stat
+                .values
+                .iter()
+                .zip(stat.deltas.iter())
+                .enumerate()
+                .map(|(i, (value, delta))| {
+                    if i == 0 {
+                        format!("{}", value / scale)
+                    } else {
+                        format!("{} ({:+})", value / scale, delta / scale)
+                    }
+                })
+                .collect();
+            output.push_str(&format!(
+                "| {} | {} |\n",
+                markdown_escape(&stat.name),
+                cells.join(" | ")
+            ));
+        }
+        output.push('\n');
+
+        output.push_str("## Trades\n\n");
+        for diff in &self.trades {
+            output.push_str(&format!(
+                "- {}: {} only in {}, {} only in {}\n",
+                markdown_escape(&diff.backtest_name),
+                diff.only_in_baseline.len(),
+                names.first().map(String::as_str).unwrap_or(""),
+                diff.only_in_other.len(),
+                markdown_escape(&diff.backtest_name)
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    #[test]
+    fn test_compare() -> Result<()> {
+        let baseline = load_backtest();
+        let mut other = load_backtest();
+        other.metadata.backtest_name = "testing_other".to_string();
+        other.metadata.parameters.strategy_name = "meanreversion".to_string();
+        other.period_timeseries_stats.pop();
+        other.trades.clear();
+
+        // Test
+        let comparison = compare(&[baseline.clone(), other])?;
+
+        // Validate
+        assert_eq!(
+            comparison.parameters,
+            vec![ParameterDiff {
+                name: "strategy_name".to_string(),
+                values: vec![
+                    "cointegrationzscore".to_string(),
+                    "meanreversion".to_string()
+                ],
+            }]
+        );
+        assert!(comparison
+            .stats
+            .iter()
+            .all(|stat| stat.deltas.iter().all(|delta| *delta == 0.0)));
+        assert_eq!(
+            comparison.equity.len(),
+            baseline.period_timeseries_stats.len()
+        );
+        assert_eq!(comparison.equity.last().unwrap().values[1], None);
+        assert_eq!(
+            comparison.trades[0].only_in_baseline.len(),
+            baseline.trades.len()
+        );
+        assert!(comparison.trades[0].only_in_other.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_align_equity_unsorted() {
+        let mut baseline = load_backtest();
+        let mut other = load_backtest();
+        baseline.period_timeseries_stats.reverse();
+        other.period_timeseries_stats.remove(0);
+
+        // Test
+        let equity = align_equity(&[baseline.clone(), other]);
+
+        // Validate
+        let mut timestamps: Vec<i64> = baseline
+            .period_timeseries_stats
+            .iter()
+            .map(|stat| stat.timestamp)
+            .collect();
+        timestamps.sort();
+        assert_eq!(
+            equity
+                .iter()
+                .map(|point| point.timestamp)
+                .collect::<Vec<i64>>(),
+            timestamps
+        );
+        assert_eq!(equity[0].values[1], None);
+        assert!(equity[1..].iter().all(|point| point.values[1].is_some()));
+    }
+
+    #[test]
+    fn test_trade_diff_duplicates() -> Result<()> {
+        let mut baseline = load_backtest();
+        let trade = baseline.trades[0].clone();
+        baseline.trades.push(trade.clone());
+        baseline.trades.push(trade);
+        let mut other = load_backtest();
+        other.metadata.backtest_name = "testing_other".to_string();
+
+        // Test
+        let comparison = compare(&[baseline, other])?;
+
+        // Validate
+        assert_eq!(comparison.trades[0].only_in_baseline.len(), 2);
+        assert!(comparison.trades[0].only_in_other.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_renderers() -> Result<()> {
+        let baseline = load_backtest();
+        let mut other = load_backtest();
+        other.metadata.backtest_name = "testing|other".to_string();
+        let comparison = compare(&[baseline, other])?;
+
+        // Test
+        let json = comparison.to_json()?;
+        let markdown = comparison.to_markdown(&ReportConfig::default());
+
+        // Validate
+        assert!(json.contains("\"backtest_names\""));
+        assert!(markdown.contains("| | testing76543 | testing\\|other |"));
+        assert!(markdown.contains("No parameter changes."));
+        assert!(markdown.contains("| sharpe_ratio | 6.771709 | 6.771709 (+0) |"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use mbinary::symbols::Instrument;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+const MONTH_CODES: &str = "FGHJKMNQUVXZ";
+
+/// How the front month is chosen among the unexpired contracts of a chain.
+#[derive(Debug, Clone)]
+pub enum RollRule {
+    /// Roll to the next contract `days_before_expiration` days before the front expires.
+    Calendar { days_before_expiration: i64 },
+    /// Roll to a later contract once it trades more volume on a day than the front, keyed by
+    /// `(instrument_id, day)`. Rolls are one-way, the front never moves back to an earlier
+    /// contract.
+    Volume(HashMap<(u32, NaiveDate), u64>),
+}
+
+/// Front month change within a roll schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollEvent {
+    pub ts: i64,
+    pub from: Option<u32>,
+    pub to: u32,
+}
+
+/// Raw contracts of a single root symbol, e.g. `HE`, ordered by expiration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractChain {
+    pub root: String,
+    pub contracts: Vec<Instrument>,
+}
+
+/// Splits a futures ticker such as `HEG4` or `ZCH24` into its root symbol.
+pub fn root_symbol(ticker: &str) -> Option<&str> {
+    let without_year = ticker.trim_end_matches(|c: char| c.is_ascii_digit());
+    if without_year.len() == ticker.len() {
+        return None;
+    }
+
+    let (month_index, _) = without_year.char_indices().last()?;
+    if month_index == 0 {
+        return None;
+    }
+
+    let (root, month) = without_year.split_at(month_index);
+    if MONTH_CODES.contains(month) {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// Groups the raw futures contracts in `instruments` into chains per root symbol.
+pub fn build_chains(instruments: &[Instrument]) -> Vec<ContractChain> {
+    let mut chains: Vec<ContractChain> = Vec::new();
+
+    for instrument in instruments.iter().filter(|i| !i.is_continuous) {
+        let root = match root_symbol(&instrument.ticker) {
+            Some(root) => root,
+            None => continue,
+        };
+
+        match chains.iter_mut().find(|chain| chain.root == root) {
+            Some(chain) => chain.contracts.push(instrument.clone()),
+            None => chains.push(ContractChain {
+                root: root.to_string(),
+                contracts: vec![instrument.clone()],
+            }),
+        }
+    }
+
+    for chain in chains.iter_mut() {
+        chain
+            .contracts
+            .sort_by_key(|contract| contract.expiration_date);
+    }
+    chains.sort_by(|a, b| a.root.cmp(&b.root));
+
+    chains
+}
+
+impl ContractChain {
+    /// Contracts not yet expired at `ts`, nearest expiration first.
+    fn unexpired(&self, ts: i64) -> impl Iterator<Item = &Instrument> {
+        self.contracts
+            .iter()
+            .filter(move |contract| (contract.expiration_date as i64) > ts)
+    }
+
+    /// Front month contract at `ts` under `rule`.
+    pub fn front_month(&self, ts: i64, rule: &RollRule) -> Option<&Instrument> {
+        match rule {
+            RollRule::Calendar {
+                days_before_expiration,
+            } => {
+                let offset = days_before_expiration * NANOS_PER_DAY;
+                self.unexpired(ts)
+                    .find(|contract| contract.expiration_date as i64 - offset > ts)
+            }
+            RollRule::Volume(volumes) => self.volume_front(ts, volumes),
+        }
+    }
+
+    /// Replays the daily volumes up to `ts`, rolling to the most traded later contract
+    /// whenever it out-trades the current front.
+    fn volume_front(
+        &self,
+        ts: i64,
+        volumes: &HashMap<(u32, NaiveDate), u64>,
+    ) -> Option<&Instrument> {
+        let volume = |contract: &Instrument, day: NaiveDate| {
+            contract
+                .instrument_id
+                .and_then(|id| volumes.get(&(id, day)))
+                .copied()
+                .unwrap_or(0)
+        };
+        let expired = |contract: &Instrument, ts: i64| contract.expiration_date as i64 <= ts;
+
+        let last_day = Utc.timestamp_nanos(ts).date_naive();
+        let days: BTreeSet<NaiveDate> = volumes
+            .keys()
+            .map(|(_, day)| *day)
+            .filter(|day| *day <= last_day)
+            .collect();
+
+        let mut front = 0;
+        for day in days {
+            let day_ts = day_to_unix_nanos(day);
+            while front < self.contracts.len() && expired(&self.contracts[front], day_ts) {
+                front += 1;
+            }
+            let current = match self.contracts.get(front) {
+                Some(contract) => volume(contract, day),
+                None => return None,
+            };
+
+            let busiest = self.contracts[front + 1..]
+                .iter()
+                .enumerate()
+                .filter(|(_, contract)| !expired(contract, day_ts))
+                .max_by_key(|(offset, contract)| {
+                    (volume(contract, day), std::cmp::Reverse(*offset))
+                });
+
+            if let Some((offset, contract)) = busiest {
+                if volume(contract, day) > current {
+                    front += offset + 1;
+                }
+            }
+        }
+
+        self.contracts[front.min(self.contracts.len())..]
+            .iter()
+            .find(|contract| !expired(contract, ts))
+    }
+
+    /// Contract following the front month at `ts` under `rule`.
+    pub fn next_month(&self, ts: i64, rule: &RollRule) -> Option<&Instrument> {
+        let front = self.front_month(ts, rule)?;
+        self.contracts
+            .iter()
+            .find(|contract| contract.expiration_date > front.expiration_date)
+    }
+
+    /// Front month changes between `start` and `end`, evaluated daily at midnight UTC.
+    pub fn roll_schedule(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        rule: &RollRule,
+    ) -> Vec<RollEvent> {
+        let mut events = Vec::new();
+        let mut current: Option<u32> = None;
+
+        for day in start.iter_days().take_while(|day| *day <= end) {
+            let ts = day_to_unix_nanos(day);
+            let front = match self
+                .front_month(ts, rule)
+                .and_then(|contract| contract.instrument_id)
+            {
+                Some(id) => id,
+                None => continue,
+            };
+
+            if current != Some(front) {
+                events.push(RollEvent {
+                    ts,
+                    from: current,
+                    to: front,
+                });
+                current = Some(front);
+            }
+        }
+
+        events
+    }
+}
+
+/// Instrument id of the front month at `ts` according to `schedule`.
+pub fn contract_at(schedule: &[RollEvent], ts: i64) -> Option<u32> {
+    schedule
+        .iter()
+        .take_while(|event| event.ts <= ts)
+        .last()
+        .map(|event| event.to)
+}
+
+/// Builds a continuous series from raw contract records, keeping each record only while its
+/// contract is the front month. `key` returns the `(instrument_id, ts)` of a record.
+pub fn stitch_continuous<T, F>(records: Vec<T>, schedule: &[RollEvent], key: F) -> Vec<T>
+where
+    F: Fn(&T) -> (u32, i64),
+{
+    let mut stitched: Vec<T> = records
+        .into_iter()
+        .filter(|record| {
+            let (instrument_id, ts) = key(record);
+            contract_at(schedule, ts) == Some(instrument_id)
+        })
+        .collect();
+
+    stitched.sort_by_key(|record| key(record).1);
+    stitched
+}
+
+fn day_to_unix_nanos(day: NaiveDate) -> i64 {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap(); // Set time to midnight
+    let datetime: DateTime<Utc> = DateTime::from_naive_utc_and_offset(midnight, Utc);
+    datetime.timestamp_nanos_opt().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::date_to_unix_nanos;
+    use mbinary::enums::Dataset;
+    use mbinary::vendors::Vendors;
+
+    fn dummy_contract(id: u32, ticker: &str, expiration: &str) -> Instrument {
+        Instrument::new(
+            Some(id),
+            ticker,
+            "Lean hogs",
+            Dataset::Futures,
+            Vendors::Databento,
+            1,
+            1,
+            1,
+            date_to_unix_nanos(expiration).unwrap() as u64,
+            false,
+            true,
+        )
+    }
+
+    fn dummy_chain() -> ContractChain {
+        let instruments = vec![
+            dummy_contract(2, "HEJ4", "2024-04-12"),
+            dummy_contract(1, "HEG4", "2024-02-14"),
+            dummy_contract(3, "ZCH4", "2024-03-14"),
+        ];
+        build_chains(&instruments).remove(0)
+    }
+
+    fn date(date_str: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_root_symbol() {
+        assert_eq!(root_symbol("HEG4"), Some("HE"));
+        assert_eq!(root_symbol("ZCH24"), Some("ZC"));
+        assert_eq!(root_symbol("AAPL"), None);
+        assert_eq!(root_symbol("HE.n.0"), None);
+        assert_eq!(root_symbol(""), None);
+        assert_eq!(root_symbol("4"), None);
+        assert_eq!(root_symbol("H4"), None);
+        assert_eq!(root_symbol("HEé4"), None);
+    }
+
+    #[test]
+    fn test_build_chains() {
+        let instruments = vec![
+            dummy_contract(2, "HEJ4", "2024-04-12"),
+            dummy_contract(1, "HEG4", "2024-02-14"),
+            dummy_contract(3, "ZCH4", "2024-03-14"),
+        ];
+
+        // Test
+        let chains = build_chains(&instruments);
+
+        // Validate
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].root, "HE");
+        assert_eq!(chains[0].contracts[0].ticker, "HEG4");
+        assert_eq!(chains[0].contracts[1].ticker, "HEJ4");
+        assert_eq!(chains[1].root, "ZC");
+    }
+
+    #[test]
+    fn test_front_month_calendar() -> anyhow::Result<()> {
+        let chain = dummy_chain();
+        let rule = RollRule::Calendar {
+            days_before_expiration: 5,
+        };
+
+        // Test
+        let early = chain.front_month(date_to_unix_nanos("2024-02-01")?, &rule);
+        let rolled = chain.front_month(date_to_unix_nanos("2024-02-10")?, &rule);
+        let next = chain.next_month(date_to_unix_nanos("2024-02-01")?, &rule);
+
+        // Validate
+        assert_eq!(early.unwrap().ticker, "HEG4");
+        assert_eq!(rolled.unwrap().ticker, "HEJ4");
+        assert_eq!(next.unwrap().ticker, "HEJ4");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_front_month_volume() -> anyhow::Result<()> {
+        let chain = dummy_chain();
+        let mut volumes = HashMap::new();
+        volumes.insert((1, date("2024-02-01")), 100);
+        volumes.insert((2, date("2024-02-01")), 50);
+        volumes.insert((1, date("2024-02-02")), 100);
+        volumes.insert((2, date("2024-02-02")), 150);
+        volumes.insert((1, date("2024-02-03")), 200);
+        volumes.insert((2, date("2024-02-03")), 50);
+        let rule = RollRule::Volume(volumes);
+
+        // Test
+        let before = chain.front_month(date_to_unix_nanos("2024-02-01")?, &rule);
+        let after = chain.front_month(date_to_unix_nanos("2024-02-02")?, &rule);
+        let sticky = chain.front_month(date_to_unix_nanos("2024-02-03")?, &rule);
+
+        // Validate
+        assert_eq!(before.unwrap().ticker, "HEG4");
+        assert_eq!(after.unwrap().ticker, "HEJ4");
+        assert_eq!(sticky.unwrap().ticker, "HEJ4");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_schedule_and_stitch() -> anyhow::Result<()> {
+        let chain = dummy_chain();
+        let rule = RollRule::Calendar {
+            days_before_expiration: 5,
+        };
+
+        // Test
+        let schedule = chain.roll_schedule(date("2024-02-01"), date("2024-02-20"), &rule);
+        let records = vec![
+            (1, date_to_unix_nanos("2024-02-05")?),
+            (2, date_to_unix_nanos("2024-02-05 12:00:00")?),
+            (1, date_to_unix_nanos("2024-02-12")?),
+            (2, date_to_unix_nanos("2024-02-12 12:00:00")?),
+        ];
+        let continuous = stitch_continuous(records, &schedule, |record| *record);
+
+        // Validate
+        assert_eq!(
+            schedule,
+            vec![
+                RollEvent {
+                    ts: date_to_unix_nanos("2024-02-01")?,
+                    from: None,
+                    to: 1,
+                },
+                RollEvent {
+                    ts: date_to_unix_nanos("2024-02-09")?,
+                    from: Some(1),
+                    to: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            continuous,
+            vec![
+                (1, date_to_unix_nanos("2024-02-05")?),
+                (2, date_to_unix_nanos("2024-02-12 12:00:00")?),
+            ]
+        );
+
+        Ok(())
+    }
+}
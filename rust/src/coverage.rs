@@ -0,0 +1,196 @@
+use chrono::{Datelike, NaiveDate, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Data available on the server for a single instrument and schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coverage {
+    pub symbol: String,
+    pub instrument_id: u32,
+    pub first_ts: i64,
+    pub last_ts: i64,
+    pub days: Vec<NaiveDate>,
+}
+
+impl Coverage {
+    pub fn first_day(&self) -> NaiveDate {
+        Utc.timestamp_nanos(self.first_ts).date_naive()
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        Utc.timestamp_nanos(self.last_ts).date_naive()
+    }
+
+    /// Weekdays in `[start, end]` without data, e.g. the days still to be backfilled.
+    pub fn missing_days(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        let covered: BTreeSet<&NaiveDate> = self.days.iter().collect();
+
+        start
+            .iter_days()
+            .take_while(|day| *day <= end)
+            .filter(|day| is_weekday(day) && !covered.contains(day))
+            .collect()
+    }
+
+    /// Ranges of consecutive missing weekdays between the first and last covered day.
+    pub fn gaps(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        if self.days.is_empty() {
+            return vec![];
+        }
+
+        let mut gaps: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+        let mut previous: Option<NaiveDate> = None;
+
+        for day in self.missing_days(self.first_day(), self.last_day()) {
+            match (gaps.last_mut(), previous) {
+                (Some(gap), Some(prev)) if next_weekday(prev) == day => gap.1 = day,
+                _ => gaps.push((day, day)),
+            }
+            previous = Some(day);
+        }
+
+        gaps
+    }
+
+    /// Calendar-style map with one row per month, '#' a covered day, '.' a missing
+    /// weekday and ' ' a weekend or a day outside the covered range.
+    pub fn calendar(&self) -> String {
+        let mut output = format!("{} ({})\n", self.symbol, self.instrument_id);
+
+        if self.days.is_empty() {
+            output.push_str("  no data\n");
+            return output;
+        }
+
+        let first = self.first_day();
+        let last = self.last_day();
+        let covered: BTreeSet<&NaiveDate> = self.days.iter().collect();
+
+        output.push_str(&format!("  {} to {}\n", first, last));
+        output.push_str("          1234567890123456789012345678901\n");
+
+        let mut month_start = first.with_day(1).unwrap_or(first);
+        while month_start <= last {
+            let mut row = format!("  {} ", month_start.format("%Y-%m"));
+
+            for day in month_start
+                .iter_days()
+                .take_while(|day| day.month() == month_start.month())
+            {
+                let cell = if covered.contains(&day) {
+                    '#'
+                } else if day < first || day > last || !is_weekday(&day) {
+                    ' '
+                } else {
+                    '.'
+                };
+                row.push(cell);
+            }
+
+            output.push_str(row.trim_end());
+            output.push('\n');
+
+            month_start = match month_start.checked_add_months(chrono::Months::new(1)) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        output
+    }
+}
+
+fn is_weekday(day: &NaiveDate) -> bool {
+    !matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn next_weekday(day: NaiveDate) -> NaiveDate {
+    let mut next = day;
+    loop {
+        next = match next.succ_opt() {
+            Some(next) => next,
+            None => return day,
+        };
+        if is_weekday(&next) {
+            return next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::date_to_unix_nanos;
+
+    fn date(date_str: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap()
+    }
+
+    fn dummy_coverage() -> Coverage {
+        // 2024-01-01 is a Monday
+        Coverage {
+            symbol: "ES.n.0".to_string(),
+            instrument_id: 1,
+            first_ts: date_to_unix_nanos("2024-01-01").unwrap(),
+            last_ts: date_to_unix_nanos("2024-01-12 23:00:00").unwrap(),
+            days: vec![
+                date("2024-01-01"),
+                date("2024-01-02"),
+                date("2024-01-05"),
+                date("2024-01-09"),
+                date("2024-01-10"),
+                date("2024-01-11"),
+                date("2024-01-12"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_missing_days() {
+        let coverage = dummy_coverage();
+
+        // Test
+        let missing = coverage.missing_days(date("2024-01-01"), date("2024-01-16"));
+
+        // Validate
+        assert_eq!(
+            missing,
+            vec![
+                date("2024-01-03"),
+                date("2024-01-04"),
+                date("2024-01-08"),
+                date("2024-01-15"),
+                date("2024-01-16"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        let coverage = dummy_coverage();
+
+        // Test
+        let gaps = coverage.gaps();
+
+        // Validate
+        assert_eq!(
+            gaps,
+            vec![
+                (date("2024-01-03"), date("2024-01-04")),
+                (date("2024-01-08"), date("2024-01-08")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        let coverage = dummy_coverage();
+
+        // Test
+        let calendar = coverage.calendar();
+
+        // Validate
+        let expected = "ES.n.0 (1)\n  2024-01-01 to 2024-01-12\n          1234567890123456789012345678901\n  2024-01 ##..#  .####\n";
+        assert_eq!(calendar, expected);
+    }
+}
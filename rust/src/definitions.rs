@@ -0,0 +1,151 @@
+use crate::error::Result;
+use dbn::decode::{DbnDecoder, DbnMetadata, DecodeRecord};
+use dbn::InstrumentDefMsg;
+use mbinary::enums::Dataset;
+use mbinary::symbols::Instrument;
+use mbinary::vendors::{DatabentoData, VendorData, Vendors};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Reads a Databento DBN definition file into instruments, keeping the latest definition of
+/// each raw symbol. `schema` is the Databento schema the instruments are ingested with and
+/// instruments expiring before `as_of` (unix nanoseconds) are marked inactive. The vendor
+/// data always records `SType::RawSymbol`, as instruments are keyed on their raw symbol.
+///
+/// `first_available` is the activation of the definition and `last_available` the event time
+/// of the latest definition seen.
+pub fn load_definitions<P: AsRef<Path>>(
+    path: P,
+    dataset: Dataset,
+    schema: dbn::Schema,
+    as_of: u64,
+) -> Result<Vec<Instrument>> {
+    let mut decoder = DbnDecoder::from_file(path)?;
+    let metadata = decoder.metadata().clone();
+
+    let vendor_data = VendorData::Databento(DatabentoData {
+        schema,
+        dataset: dbn::Dataset::from_str(&metadata.dataset)?,
+        stype: dbn::SType::RawSymbol,
+    });
+    let encoded = vendor_data.encode();
+
+    let mut instruments: Vec<Instrument> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    while let Some(definition) = decoder.decode_record::<InstrumentDefMsg>()? {
+        let instrument = definition_to_instrument(definition, dataset, encoded, as_of)?;
+
+        match index.get(&instrument.ticker) {
+            Some(&i) => instruments[i] = instrument,
+            None => {
+                index.insert(instrument.ticker.clone(), instruments.len());
+                instruments.push(instrument);
+            }
+        }
+    }
+
+    Ok(instruments)
+}
+
+fn definition_to_instrument(
+    definition: &InstrumentDefMsg,
+    dataset: Dataset,
+    vendor_data: u64,
+    as_of: u64,
+) -> Result<Instrument> {
+    let ticker = definition.raw_symbol()?;
+    let name = match definition.asset() {
+        Ok(asset) if !asset.is_empty() => asset,
+        _ => ticker,
+    };
+
+    Ok(Instrument::new(
+        None,
+        ticker,
+        name,
+        dataset,
+        Vendors::Databento,
+        vendor_data,
+        definition.hd.ts_event,
+        definition.activation,
+        definition.expiration,
+        false,
+        definition.expiration >= as_of,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::date_to_unix_nanos;
+
+    const DEFINITIONS: &str = "tests/data/test_data.definitions.dbn";
+
+    #[test]
+    fn test_load_definitions() -> anyhow::Result<()> {
+        let as_of = date_to_unix_nanos("2024-03-01")? as u64;
+
+        // Test
+        let instruments =
+            load_definitions(DEFINITIONS, Dataset::Futures, dbn::Schema::Mbp1, as_of)?;
+
+        // Validate
+        assert_eq!(instruments.len(), 2);
+        assert_eq!(instruments[0].ticker, "HEG4");
+        assert_eq!(instruments[0].name, "HE");
+        assert_eq!(instruments[0].vendor, Vendors::Databento);
+        assert!(!instruments[0].active);
+        assert_eq!(instruments[1].ticker, "HEJ4");
+        assert!(instruments[1].active);
+
+        match instruments[0].get_vendor_data() {
+            VendorData::Databento(data) => {
+                assert_eq!(data.schema, dbn::Schema::Mbp1);
+                assert_eq!(data.dataset, dbn::Dataset::GlbxMdp3);
+                assert_eq!(data.stype, dbn::SType::RawSymbol);
+            }
+            other => panic!("Expected Databento vendor data, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_definitions_availability() -> anyhow::Result<()> {
+        // Test
+        let instruments = load_definitions(DEFINITIONS, Dataset::Futures, dbn::Schema::Mbp1, 0)?;
+
+        // Validate
+        let heg4 = &instruments[0];
+        assert_eq!(
+            heg4.first_available,
+            date_to_unix_nanos("2023-10-16")? as u64
+        );
+        assert_eq!(
+            heg4.last_available,
+            date_to_unix_nanos("2024-01-03")? as u64
+        );
+        assert_eq!(
+            heg4.expiration_date,
+            date_to_unix_nanos("2024-02-14")? as u64
+        );
+
+        let hej4 = &instruments[1];
+        assert_eq!(
+            hej4.first_available,
+            date_to_unix_nanos("2023-12-15")? as u64
+        );
+        assert_eq!(
+            hej4.last_available,
+            date_to_unix_nanos("2024-01-02")? as u64
+        );
+        assert_eq!(
+            hej4.expiration_date,
+            date_to_unix_nanos("2024-04-12")? as u64
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,574 @@
+use crate::error::Result;
+use crate::export::{trade_rows, TradeRow, PRICE_SCALE};
+use mbinary::backtest::{BacktestData, Signals, Trades};
+use mbinary::live::LiveData;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// `tolerance` is how far apart live and backtest timestamps may be to still be aligned, in the
+/// units of the trade timestamps. Prices, fees and amounts are divided by `price_scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftConfig {
+    pub tolerance: i64,
+    pub price_scale: f64,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        DriftConfig {
+            tolerance: 0,
+            price_scale: PRICE_SCALE,
+        }
+    }
+}
+
+/// Live trade aligned with its backtest counterpart. Slippage is per unit and positive when
+/// the live fill was worse, the quantity difference is non-zero on partial fills.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchedTrade {
+    pub ticker: String,
+    pub action: String,
+    pub live_timestamp: i64,
+    pub backtest_timestamp: i64,
+    pub live_price: f64,
+    pub backtest_price: f64,
+    pub live_quantity: f64,
+    pub backtest_quantity: f64,
+    pub quantity_difference: f64,
+    pub slippage: f64,
+    pub slippage_cost: f64,
+    pub fee_difference: f64,
+}
+
+/// Trade or signal instruction as aligned between live and backtest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignalKey {
+    pub timestamp: i64,
+    pub ticker: String,
+    pub action: String,
+}
+
+/// PnL net of fees of both sides at a timestamp, open positions marked to market.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PnlPoint {
+    pub timestamp: i64,
+    pub live: f64,
+    pub backtest: f64,
+    pub divergence: f64,
+}
+
+/// How a live session deviated from its backtest.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub matched_trades: Vec<MatchedTrade>,
+    /// Backtest trades with no live counterpart.
+    pub missed_trades: Vec<TradeRow>,
+    /// Live trades with no backtest counterpart.
+    pub extra_trades: Vec<TradeRow>,
+    pub matched_signals: usize,
+    pub missed_signals: Vec<SignalKey>,
+    pub extra_signals: Vec<SignalKey>,
+    pub total_slippage_cost: f64,
+    pub total_fee_difference: f64,
+    pub pnl: Vec<PnlPoint>,
+}
+
+struct Alignment {
+    matched: Vec<(usize, usize)>,
+    missed: Vec<usize>,
+    extra: Vec<usize>,
+}
+
+pub fn analyze(live: &LiveData, backtest: &BacktestData, config: &DriftConfig) -> DriftReport {
+    let trades = align(
+        &trade_keys(&live.trades),
+        &trade_keys(&backtest.trades),
+        config.tolerance,
+    );
+
+    let matched_trades: Vec<MatchedTrade> = trades
+        .matched
+        .iter()
+        .map(|(i, j)| match_trade(&live.trades[*i], &backtest.trades[*j], config.price_scale))
+        .collect();
+
+    let live_signals = signal_keys(&live.signals);
+    let backtest_signals = signal_keys(&backtest.signals);
+    let signals = align(&live_signals, &backtest_signals, config.tolerance);
+
+    DriftReport {
+        total_slippage_cost: matched_trades.iter().map(|trade| trade.slippage_cost).sum(),
+        total_fee_difference: matched_trades
+            .iter()
+            .map(|trade| trade.fee_difference)
+            .sum(),
+        matched_trades,
+        missed_trades: unmatched_rows(&backtest.trades, &trades.missed, config.price_scale),
+        extra_trades: unmatched_rows(&live.trades, &trades.extra, config.price_scale),
+        matched_signals: signals.matched.len(),
+        missed_signals: signals
+            .missed
+            .iter()
+            .map(|j| backtest_signals[*j].clone())
+            .collect(),
+        extra_signals: signals
+            .extra
+            .iter()
+            .map(|i| live_signals[*i].clone())
+            .collect(),
+        pnl: pnl_divergence(&live.trades, &backtest.trades, config.price_scale),
+    }
+}
+
+fn unmatched_rows(trades: &[Trades], indices: &[usize], price_scale: f64) -> Vec<TradeRow> {
+    let trades: Vec<Trades> = indices.iter().map(|i| trades[*i].clone()).collect();
+    trade_rows(&trades, price_scale, None)
+}
+
+fn trade_keys(trades: &[Trades]) -> Vec<SignalKey> {
+    trades
+        .iter()
+        .map(|trade| SignalKey {
+            timestamp: trade.timestamp,
+            ticker: trade.ticker.clone(),
+            action: trade.action.clone(),
+        })
+        .collect()
+}
+
+fn signal_keys(signals: &[Signals]) -> Vec<SignalKey> {
+    signals
+        .iter()
+        .flat_map(|signal| {
+            signal
+                .trade_instructions
+                .iter()
+                .map(move |instruction| SignalKey {
+                    timestamp: signal.timestamp,
+                    ticker: instruction.ticker.clone(),
+                    action: instruction.action.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Pairs live and backtest items of the same ticker and action within the tolerance, closest
+/// pairs first, so an early live item can't take the counterpart of a closer later one.
+/// Candidates are only collected within the tolerance window of each ticker and action.
+fn align(live: &[SignalKey], backtest: &[SignalKey], tolerance: i64) -> Alignment {
+    // (ticker, lowercased action) -> [live indices, backtest indices]
+    let mut groups: HashMap<(&str, String), [Vec<usize>; 2]> = HashMap::new();
+    for (i, key) in live.iter().enumerate() {
+        let group = (key.ticker.as_str(), key.action.to_ascii_lowercase());
+        groups.entry(group).or_default()[0].push(i);
+    }
+    for (j, key) in backtest.iter().enumerate() {
+        let group = (key.ticker.as_str(), key.action.to_ascii_lowercase());
+        groups.entry(group).or_default()[1].push(j);
+    }
+
+    let mut candidates: Vec<(i64, usize, usize)> = Vec::new();
+    for [live_group, mut backtest_group] in groups.into_values() {
+        backtest_group.sort_by_key(|j| backtest[*j].timestamp);
+
+        for i in live_group {
+            let timestamp = live[i].timestamp;
+            let start = backtest_group
+                .partition_point(|j| backtest[*j].timestamp < timestamp.saturating_sub(tolerance));
+
+            for &j in backtest_group[start..]
+                .iter()
+                .take_while(|j| backtest[**j].timestamp <= timestamp.saturating_add(tolerance))
+            {
+                candidates.push(((backtest[j].timestamp - timestamp).abs(), i, j));
+            }
+        }
+    }
+    candidates.sort();
+
+    let mut live_used = vec![false; live.len()];
+    let mut backtest_used = vec![false; backtest.len()];
+    let mut matched = Vec::new();
+    for (_, i, j) in candidates {
+        if !live_used[i] && !backtest_used[j] {
+            live_used[i] = true;
+            backtest_used[j] = true;
+            matched.push((i, j));
+        }
+    }
+    matched.sort();
+
+    let extra = (0..live.len()).filter(|i| !live_used[*i]).collect();
+    let missed = (0..backtest.len()).filter(|j| !backtest_used[*j]).collect();
+
+    Alignment {
+        matched,
+        missed,
+        extra,
+    }
+}
+
+fn match_trade(live: &Trades, backtest: &Trades, price_scale: f64) -> MatchedTrade {
+    let live_price = live.avg_price as f64 / price_scale;
+    let backtest_price = backtest.avg_price as f64 / price_scale;
+    let live_quantity = live.quantity as f64;
+    let backtest_quantity = backtest.quantity as f64;
+
+    let slippage = if live.action.eq_ignore_ascii_case("BUY") {
+        live_price - backtest_price
+    } else {
+        backtest_price - live_price
+    };
+
+    MatchedTrade {
+        ticker: live.ticker.clone(),
+        action: live.action.clone(),
+        live_timestamp: live.timestamp,
+        backtest_timestamp: backtest.timestamp,
+        live_price,
+        backtest_price,
+        live_quantity,
+        backtest_quantity,
+        quantity_difference: live_quantity - backtest_quantity,
+        slippage,
+        slippage_cost: slippage * live_quantity.abs(),
+        fee_difference: (live.fees - backtest.fees) as f64 / price_scale,
+    }
+}
+
+/// Cash and open positions of one side.
+#[derive(Default)]
+struct Book<'a> {
+    cash: f64,
+    positions: HashMap<&'a str, f64>,
+}
+
+impl<'a> Book<'a> {
+    fn apply(&mut self, trade: &'a Trades) {
+        self.cash += (trade.trade_value - trade.fees) as f64;
+        *self.positions.entry(trade.ticker.as_str()).or_default() += signed_quantity(trade);
+    }
+
+    fn value(&self, marks: &HashMap<&str, f64>) -> f64 {
+        self.positions
+            .iter()
+            .map(|(ticker, quantity)| quantity * marks.get(ticker).copied().unwrap_or(0.0))
+            .sum::<f64>()
+            + self.cash
+    }
+}
+
+fn signed_quantity(trade: &Trades) -> f64 {
+    let quantity = (trade.quantity as f64).abs();
+    if trade.action.eq_ignore_ascii_case("BUY") {
+        quantity
+    } else {
+        -quantity
+    }
+}
+
+/// PnL of both sides after each timestamp with fills. Open positions are marked at the latest
+/// fill of their ticker on either side, the backtest fill on ties, so both sides share a mark
+/// and the divergence reflects execution rather than when positions were closed.
+fn pnl_divergence(live: &[Trades], backtest: &[Trades], price_scale: f64) -> Vec<PnlPoint> {
+    let mut fills: Vec<(i64, bool, &Trades)> = live
+        .iter()
+        .map(|trade| (trade.timestamp, true, trade))
+        .chain(backtest.iter().map(|trade| (trade.timestamp, false, trade)))
+        .collect();
+    // Live before backtest at equal timestamps, stable otherwise
+    fills.sort_by_key(|(timestamp, is_live, _)| (*timestamp, !*is_live));
+
+    let mut marks: HashMap<&str, f64> = HashMap::new();
+    let mut live_book = Book::default();
+    let mut backtest_book = Book::default();
+    let mut points: Vec<PnlPoint> = Vec::new();
+
+    for (index, (timestamp, is_live, trade)) in fills.iter().enumerate() {
+        let quantity = (trade.quantity as f64).abs();
+        if quantity > 0.0 {
+            // Trade value already includes any contract multiplier
+            let unit_value = (trade.trade_value as f64).abs() / quantity;
+            marks.insert(trade.ticker.as_str(), unit_value);
+        }
+
+        if *is_live {
+            live_book.apply(trade);
+        } else {
+            backtest_book.apply(trade);
+        }
+
+        if fills.get(index + 1).map(|(next, ..)| next) == Some(timestamp) {
+            continue;
+        }
+
+        let live_value = live_book.value(&marks) / price_scale;
+        let backtest_value = backtest_book.value(&marks) / price_scale;
+        points.push(PnlPoint {
+            timestamp: *timestamp,
+            live: live_value,
+            backtest: backtest_value,
+            divergence: live_value - backtest_value,
+        });
+    }
+
+    points
+}
+
+impl DriftReport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::from("# Live vs backtest drift\n\n");
+
+        output.push_str("## Summary\n\n");
+        output.push_str(&format!(
+            "- Matched trades: {}\n- Missed trades: {}\n- Extra trades: {}\n",
+            self.matched_trades.len(),
+            self.missed_trades.len(),
+            self.extra_trades.len()
+        ));
+        output.push_str(&format!(
+            "- Matched signals: {}\n- Missed signals: {}\n- Extra signals: {}\n",
+            self.matched_signals,
+            self.missed_signals.len(),
+            self.extra_signals.len()
+        ));
+        output.push_str(&format!(
+            "- Total slippage cost: {}\n- Total fee difference: {}\n",
+            self.total_slippage_cost, self.total_fee_difference
+        ));
+        if let Some(last) = self.pnl.last() {
+            output.push_str(&format!("- Final PnL divergence: {}\n", last.divergence));
+        }
+        output.push('\n');
+
+        output.push_str("## Matched trades\n\n");
+        output.push_str(
+            "| Ticker | Action | Live ts | Backtest ts | Live price | Backtest price | Slippage | Fee diff | Qty diff |\n",
+        );
+        output.push_str("|---|---|---|---|---|---|---|---|---|\n");
+        for trade in &self.matched_trades {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                trade.ticker,
+                trade.action,
+                trade.live_timestamp,
+                trade.backtest_timestamp,
+                trade.live_price,
+                trade.backtest_price,
+                trade.slippage,
+                trade.fee_difference,
+                trade.quantity_difference
+            ));
+        }
+        output.push('\n');
+
+        output.push_str("## Unmatched trades\n\n");
+        for trade in &self.missed_trades {
+            output.push_str(&format!(
+                "- Missed: {} {} {} @ {} ({})\n",
+                trade.action, trade.quantity, trade.ticker, trade.avg_price, trade.timestamp
+            ));
+        }
+        for trade in &self.extra_trades {
+            output.push_str(&format!(
+                "- Extra: {} {} {} @ {} ({})\n",
+                trade.action, trade.quantity, trade.ticker, trade.avg_price, trade.timestamp
+            ));
+        }
+        output.push('\n');
+
+        output.push_str("## PnL divergence\n\n");
+        output.push_str("| Timestamp | Live | Backtest | Divergence |\n");
+        output.push_str("|---|---|---|---|\n");
+        for point in &self.pnl {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                point.timestamp, point.live, point.backtest, point.divergence
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    fn load_live() -> LiveData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    #[test]
+    fn test_analyze() {
+        let backtest = load_backtest();
+        let mut live = load_live();
+        live.trades[0].avg_price = 13080;
+
+        let mut extra = live.trades[0].clone();
+        extra.ticker = "MSFT".to_string();
+        live.trades.push(extra);
+
+        let config = DriftConfig {
+            price_scale: 1.0,
+            ..Default::default()
+        };
+
+        // Test
+        let report = analyze(&live, &backtest, &config);
+
+        // Validate
+        assert_eq!(report.matched_trades.len(), 1);
+        assert_eq!(report.matched_trades[0].slippage, 6.0);
+        assert_eq!(report.matched_trades[0].quantity_difference, 0.0);
+        assert_eq!(report.total_slippage_cost, 24.0);
+        assert_eq!(report.total_fee_difference, -12.0);
+        assert!(report.missed_trades.is_empty());
+        assert_eq!(report.extra_trades.len(), 1);
+        assert_eq!(report.matched_signals, 2);
+        assert_eq!(report.pnl.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_scaled_partial_fill() {
+        let backtest = load_backtest();
+        let mut live = load_live();
+        live.trades[0].avg_price = 13174;
+        live.trades[0].quantity = 1;
+        let config = DriftConfig {
+            price_scale: 100.0,
+            ..Default::default()
+        };
+
+        // Test
+        let report = analyze(&live, &backtest, &config);
+
+        // Validate
+        let trade = &report.matched_trades[0];
+        assert_eq!(trade.live_price, 131.74);
+        assert_eq!(trade.backtest_price, 130.74);
+        assert!((trade.slippage - 1.0).abs() < 1e-9);
+        assert_eq!(trade.quantity_difference, -3.0);
+        assert_eq!(trade.fee_difference, -0.12);
+    }
+
+    #[test]
+    fn test_pnl_marks_open_positions() {
+        let fill = |timestamp: i64, action: &str, trade_value: i64| {
+            let mut trade = load_backtest().trades[0].clone();
+            trade.timestamp = timestamp;
+            trade.action = action.to_string();
+            trade.quantity = 4;
+            trade.avg_price = trade_value.abs() / 4;
+            trade.trade_value = trade_value;
+            trade.fees = 0;
+            trade
+        };
+        let backtest = vec![fill(1, "BUY", -400), fill(3, "SELL", 440)];
+        let live = vec![fill(1, "BUY", -404)];
+
+        // Test
+        let pnl = pnl_divergence(&live, &backtest, 1.0);
+
+        // Validate
+        assert_eq!(pnl.len(), 2);
+        assert_eq!((pnl[0].live, pnl[0].backtest), (-4.0, 0.0));
+        assert_eq!((pnl[1].live, pnl[1].backtest), (36.0, 40.0));
+        assert_eq!(pnl[1].divergence, -4.0);
+    }
+
+    #[test]
+    fn test_align_with_tolerance() {
+        let key = |timestamp: i64| SignalKey {
+            timestamp,
+            ticker: "AAPL".to_string(),
+            action: "BUY".to_string(),
+        };
+        let live = vec![key(105), key(300)];
+        let backtest = vec![key(100), key(110), key(200)];
+
+        // Test
+        let alignment = align(&live, &backtest, 10);
+
+        // Validate
+        assert_eq!(alignment.matched, vec![(0, 0)]);
+        assert_eq!(alignment.extra, vec![1]);
+        assert_eq!(alignment.missed, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_align_closest_first() {
+        let key = |timestamp: i64| SignalKey {
+            timestamp,
+            ticker: "AAPL".to_string(),
+            action: "BUY".to_string(),
+        };
+        let live = vec![key(106), key(112)];
+        let backtest = vec![key(100), key(110)];
+
+        // Test
+        let alignment = align(&live, &backtest, 10);
+
+        // Validate
+        assert_eq!(alignment.matched, vec![(0, 0), (1, 1)]);
+        assert!(alignment.extra.is_empty());
+        assert!(alignment.missed.is_empty());
+    }
+
+    #[test]
+    fn test_align_groups() {
+        let key = |timestamp: i64, ticker: &str, action: &str| SignalKey {
+            timestamp,
+            ticker: ticker.to_string(),
+            action: action.to_string(),
+        };
+        let live = vec![key(100, "AAPL", "buy"), key(100, "MSFT", "SELL")];
+        let backtest = vec![
+            key(300, "AAPL", "BUY"),
+            key(101, "MSFT", "BUY"),
+            key(95, "AAPL", "BUY"),
+            key(99, "MSFT", "SELL"),
+        ];
+
+        // Test
+        let alignment = align(&live, &backtest, 10);
+
+        // Validate
+        assert_eq!(alignment.matched, vec![(0, 2), (1, 3)]);
+        assert!(alignment.extra.is_empty());
+        assert_eq!(alignment.missed, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let backtest = load_backtest();
+        let live = load_live();
+        let config = DriftConfig {
+            price_scale: 1.0,
+            ..Default::default()
+        };
+        let report = analyze(&live, &backtest, &config);
+
+        // Test
+        let markdown = report.to_markdown();
+
+        // Validate
+        assert!(markdown.contains("- Matched trades: 1"));
+        assert!(
+            markdown.contains("| AAPL | BUY | 1704903000 | 1704903000 | 13074 | 13074 | 0 | -12 |")
+        );
+    }
+}
@@ -18,8 +18,18 @@ pub enum Error {
     InvalidDateFormat(String),
     #[error("Custom error: {0}")]
     CustomError(String),
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("DBN error: {0}")]
+    DbnError(#[from] dbn::Error),
     #[error("Mbinary error: {0}")]
     MbinaryError(#[from] mbinary::Error),
+    #[cfg(feature = "parquet")]
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow_schema::ArrowError),
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
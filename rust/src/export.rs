@@ -0,0 +1,479 @@
+use crate::cache::InstrumentCache;
+use crate::error::{Error, Result};
+use mbinary::backtest::{Signals, Trades};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+#[cfg(feature = "parquet")]
+use {
+    arrow_json::ReaderBuilder,
+    arrow_schema::{DataType, Field, Schema},
+    parquet::arrow::ArrowWriter,
+    std::sync::Arc,
+};
+
+/// Fixed-point scale of prices and amounts, 1 unit = 1e-9.
+pub const PRICE_SCALE: f64 = 1_000_000_000.0;
+
+/// Output formats of the exports. Parquet needs the `parquet` feature, which pulls in arrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Format from the file extension, `.csv`, `.ndjson`, `.jsonl` or `.parquet`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            #[cfg(feature = "parquet")]
+            "parquet" => Ok(ExportFormat::Parquet),
+            #[cfg(not(feature = "parquet"))]
+            "parquet" => Err(Error::CustomError(
+                "Parquet exports need the `parquet` feature.".to_string(),
+            )),
+            _ => Err(Error::CustomError(format!(
+                "Unsupported export format '{}', expected csv, ndjson or parquet.",
+                extension
+            ))),
+        }
+    }
+}
+
+/// Trade with prices and amounts scaled, ready for a blotter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradeRow {
+    pub trade_id: i32,
+    pub signal_id: i32,
+    pub timestamp: i64,
+    pub ticker: String,
+    pub instrument_id: Option<u32>,
+    pub instrument_name: Option<String>,
+    pub action: String,
+    pub quantity: f64,
+    pub avg_price: f64,
+    pub trade_value: f64,
+    pub trade_cost: f64,
+    pub fees: f64,
+}
+
+/// One trade instruction of a signal, with the signal timestamp.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalRow {
+    pub timestamp: i64,
+    pub signal_id: i32,
+    pub ticker: String,
+    pub instrument_id: Option<u32>,
+    pub instrument_name: Option<String>,
+    pub order_type: String,
+    pub action: String,
+    pub weight: f64,
+    pub quantity: f64,
+    pub limit_price: Option<f64>,
+    pub aux_price: Option<f64>,
+}
+
+/// Row written by `write_rows`.
+pub trait ExportRow: Serialize {
+    /// Columns of the row in a Parquet file.
+    #[cfg(feature = "parquet")]
+    fn fields() -> Vec<Field>;
+}
+
+impl ExportRow for TradeRow {
+    #[cfg(feature = "parquet")]
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("trade_id", DataType::Int32, false),
+            Field::new("signal_id", DataType::Int32, false),
+            Field::new("timestamp", DataType::Int64, false),
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new("instrument_id", DataType::UInt32, true),
+            Field::new("instrument_name", DataType::Utf8, true),
+            Field::new("action", DataType::Utf8, false),
+            Field::new("quantity", DataType::Float64, false),
+            Field::new("avg_price", DataType::Float64, false),
+            Field::new("trade_value", DataType::Float64, false),
+            Field::new("trade_cost", DataType::Float64, false),
+            Field::new("fees", DataType::Float64, false),
+        ]
+    }
+}
+
+impl ExportRow for SignalRow {
+    #[cfg(feature = "parquet")]
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("timestamp", DataType::Int64, false),
+            Field::new("signal_id", DataType::Int32, false),
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new("instrument_id", DataType::UInt32, true),
+            Field::new("instrument_name", DataType::Utf8, true),
+            Field::new("order_type", DataType::Utf8, false),
+            Field::new("action", DataType::Utf8, false),
+            Field::new("weight", DataType::Float64, false),
+            Field::new("quantity", DataType::Float64, false),
+            Field::new("limit_price", DataType::Float64, true),
+            Field::new("aux_price", DataType::Float64, true),
+        ]
+    }
+}
+
+/// Scales the trades, `instruments` joins the instrument id and name from the cache as loaded,
+/// refresh it beforehand.
+pub fn trade_rows(
+    trades: &[Trades],
+    price_scale: f64,
+    instruments: Option<&InstrumentCache>,
+) -> Vec<TradeRow> {
+    trades
+        .iter()
+        .map(|trade| {
+            let (instrument_id, instrument_name) = join(&trade.ticker, instruments);
+
+            TradeRow {
+                trade_id: trade.trade_id,
+                signal_id: trade.signal_id,
+                timestamp: trade.timestamp,
+                ticker: trade.ticker.clone(),
+                instrument_id,
+                instrument_name,
+                action: trade.action.clone(),
+                quantity: trade.quantity as f64,
+                avg_price: trade.avg_price as f64 / price_scale,
+                trade_value: trade.trade_value as f64 / price_scale,
+                trade_cost: trade.trade_cost as f64 / price_scale,
+                fees: trade.fees as f64 / price_scale,
+            }
+        })
+        .collect()
+}
+
+/// Flattens the trade instructions of the signals into one row each.
+pub fn signal_rows(
+    signals: &[Signals],
+    price_scale: f64,
+    instruments: Option<&InstrumentCache>,
+) -> Vec<SignalRow> {
+    signals
+        .iter()
+        .flat_map(|signal| {
+            signal.trade_instructions.iter().map(move |instruction| {
+                let (instrument_id, instrument_name) = join(&instruction.ticker, instruments);
+
+                SignalRow {
+                    timestamp: signal.timestamp,
+                    signal_id: instruction.signal_id,
+                    ticker: instruction.ticker.clone(),
+                    instrument_id,
+                    instrument_name,
+                    order_type: instruction.order_type.clone(),
+                    action: instruction.action.clone(),
+                    weight: instruction.weight as f64,
+                    quantity: instruction.quantity as f64,
+                    limit_price: scale_str(&instruction.limit_price, price_scale),
+                    aux_price: scale_str(&instruction.aux_price, price_scale),
+                }
+            })
+        })
+        .collect()
+}
+
+pub fn write_rows<W: Write, T: ExportRow>(
+    writer: W,
+    rows: &[T],
+    format: ExportFormat,
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Ndjson => {
+            let mut writer = writer;
+            for row in rows {
+                serde_json::to_writer(&mut writer, row)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+        }
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => write_parquet(writer, rows)?,
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet<W: Write, T: ExportRow>(mut writer: W, rows: &[T]) -> Result<()> {
+    let schema = Arc::new(Schema::new(T::fields()));
+    let mut decoder = ReaderBuilder::new(schema.clone()).build_decoder()?;
+    decoder.serialize(rows)?;
+
+    // ArrowWriter needs a Send writer, so the file is built in memory first
+    let mut buffer = Vec::new();
+    let mut parquet = ArrowWriter::try_new(&mut buffer, schema, None)?;
+    if let Some(batch) = decoder.flush()? {
+        parquet.write(&batch)?;
+    }
+    parquet.close()?;
+
+    writer.write_all(&buffer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the trades of a backtest or live session scaled by `price_scale`, usually
+/// `PRICE_SCALE`. The format follows the file extension.
+pub fn export_trades<P: AsRef<Path>>(
+    trades: &[Trades],
+    path: P,
+    price_scale: f64,
+    instruments: Option<&InstrumentCache>,
+) -> Result<()> {
+    let format = ExportFormat::from_path(&path)?;
+    let file = BufWriter::new(File::create(path)?);
+    write_rows(file, &trade_rows(trades, price_scale, instruments), format)
+}
+
+/// Writes the flattened signals of a backtest or live session scaled by `price_scale`, usually
+/// `PRICE_SCALE`. The format follows the file extension.
+pub fn export_signals<P: AsRef<Path>>(
+    signals: &[Signals],
+    path: P,
+    price_scale: f64,
+    instruments: Option<&InstrumentCache>,
+) -> Result<()> {
+    let format = ExportFormat::from_path(&path)?;
+    let file = BufWriter::new(File::create(path)?);
+    write_rows(
+        file,
+        &signal_rows(signals, price_scale, instruments),
+        format,
+    )
+}
+
+fn join(ticker: &str, instruments: Option<&InstrumentCache>) -> (Option<u32>, Option<String>) {
+    match instruments.and_then(|cache| cache.cached_instrument(ticker)) {
+        Some(instrument) => (instrument.instrument_id, Some(instrument.name.clone())),
+        None => (None, None),
+    }
+}
+
+fn scale_str(value: &str, price_scale: f64) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|value| value / price_scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::Instruments;
+    use mbinary::backtest::BacktestData;
+    use mbinary::enums::Dataset;
+    use mbinary::live::LiveData;
+    use mbinary::symbols::Instrument;
+    use mbinary::vendors::Vendors;
+    use std::fs;
+    use std::time::Duration;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    fn load_live() -> LiveData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    fn dummy_cache() -> InstrumentCache {
+        let client = Instruments::new("http://127.0.0.1:8080");
+        let mut cache = InstrumentCache::new(client, Dataset::Equities, Duration::from_secs(60));
+        cache.load(vec![Instrument::new(
+            Some(7),
+            "AAPL",
+            "Apple Inc.",
+            Dataset::Equities,
+            Vendors::Databento,
+            1,
+            1,
+            1,
+            1,
+            false,
+            true,
+        )]);
+        cache
+    }
+
+    #[test]
+    fn test_trade_rows() {
+        let backtest = load_backtest();
+        let cache = dummy_cache();
+
+        // Test
+        let rows = trade_rows(&backtest.trades, 100.0, Some(&cache));
+
+        // Validate
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].avg_price, 130.74);
+        assert_eq!(rows[0].instrument_id, Some(7));
+        assert_eq!(rows[0].instrument_name, Some("Apple Inc.".to_string()));
+    }
+
+    #[test]
+    fn test_signal_rows() {
+        let live = load_live();
+        let cache = dummy_cache();
+
+        // Test
+        let rows = signal_rows(&live.signals, 100.0, Some(&cache));
+
+        // Validate
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].limit_price, Some(123.45));
+        assert_eq!(rows[1].ticker, "MSFT");
+        assert_eq!(rows[1].instrument_id, None);
+    }
+
+    #[test]
+    fn test_write_rows() -> Result<()> {
+        let live = load_live();
+        let rows = signal_rows(&live.signals, 100.0, None);
+
+        // Test
+        let mut csv_output = Vec::new();
+        write_rows(&mut csv_output, &rows, ExportFormat::Csv)?;
+        let mut ndjson_output = Vec::new();
+        write_rows(&mut ndjson_output, &rows, ExportFormat::Ndjson)?;
+
+        // Validate
+        let csv_output = String::from_utf8_lossy(&csv_output);
+        let ndjson_output = String::from_utf8_lossy(&ndjson_output);
+        assert!(csv_output.starts_with("timestamp,signal_id,ticker,instrument_id"));
+        assert_eq!(csv_output.lines().count(), 3);
+        assert_eq!(ndjson_output.lines().count(), 2);
+        assert!(ndjson_output.contains("\"limit_price\":123.45"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_trades() -> Result<()> {
+        let backtest = load_backtest();
+        let cache = dummy_cache();
+        let path = "tests/test_export_trades.csv";
+
+        // Test
+        export_trades(&backtest.trades, path, 100.0, Some(&cache))?;
+
+        // Validate
+        let output = fs::read_to_string(path)?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("trade_id,signal_id,timestamp,ticker,instrument_id"));
+        assert!(lines[1].contains(",AAPL,7,Apple Inc.,"));
+        assert!(lines[1].contains(",130.74,"));
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_signals() -> Result<()> {
+        let live = load_live();
+        let path = "tests/test_export_signals.ndjson";
+
+        // Test
+        export_signals(&live.signals, path, 100.0, None)?;
+        let unsupported = export_signals(&live.signals, "tests/signals.xlsx", 100.0, None);
+
+        // Validate
+        let output = fs::read_to_string(path)?;
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.contains("\"limit_price\":123.45"));
+        assert!(matches!(unsupported, Err(Error::CustomError(_))));
+        assert!(!Path::new("tests/signals.xlsx").exists());
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path("trades.csv").ok(),
+            Some(ExportFormat::Csv)
+        );
+        assert_eq!(
+            ExportFormat::from_path("trades.jsonl").ok(),
+            Some(ExportFormat::Ndjson)
+        );
+        assert!(ExportFormat::from_path("trades.xlsx").is_err());
+        #[cfg(feature = "parquet")]
+        assert_eq!(
+            ExportFormat::from_path("trades.parquet").ok(),
+            Some(ExportFormat::Parquet)
+        );
+        #[cfg(not(feature = "parquet"))]
+        assert!(ExportFormat::from_path("trades.parquet").is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_export_parquet() -> Result<()> {
+        use arrow_array::{Array, Float64Array, StringArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let live = load_live();
+        let path = "tests/test_export_signals.parquet";
+
+        // Test
+        export_signals(&live.signals, path, 100.0, None)?;
+
+        // Validate
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        let tickers = batch
+            .column_by_name("ticker")
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+            .expect("Expected a ticker column");
+        assert_eq!(tickers.value(1), "MSFT");
+        let limit_prices = batch
+            .column_by_name("limit_price")
+            .and_then(|column| column.as_any().downcast_ref::<Float64Array>())
+            .expect("Expected a limit_price column");
+        assert_eq!(limit_prices.value(0), 123.45);
+        assert!(batch.column_by_name("instrument_id").unwrap().is_null(0));
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -1,17 +1,106 @@
-use crate::response::ApiResponse;
+use crate::coverage::Coverage;
+use crate::response::{ApiDefault, ApiResponse};
+use crate::symbology::SymbolResolution;
 use crate::{error::Error, error::Result};
 use futures_util::StreamExt;
+use mbinary::encode::CombinedEncoder;
+use mbinary::enums::{Dataset, Schema, Stype};
+use mbinary::metadata::Metadata;
 use mbinary::params::RetrieveParams;
+use mbinary::record_ref::RecordRef;
 use reqwest::StatusCode;
-use reqwest::{self, Client, ClientBuilder};
+use reqwest::{self, Client, ClientBuilder, Response};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Write;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Default size in bytes of a single batch sent by the batched upload methods.
+pub const DEFAULT_BATCH_SIZE: usize = 8 * 1024 * 1024;
+
+/// Estimated size of the records returned for a single symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolEstimate {
+    pub symbol: String,
+    pub records: u64,
+    pub bytes: u64,
+}
+
+/// Estimated size of the records a `RetrieveParams` query would return.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryEstimate {
+    pub symbols: Vec<SymbolEstimate>,
+    pub total_records: u64,
+    pub total_bytes: u64,
+}
+
+impl ApiDefault for QueryEstimate {
+    fn default_value() -> Self {
+        QueryEstimate {
+            symbols: vec![],
+            total_records: 0,
+            total_bytes: 0,
+        }
+    }
+}
+
+/// Records of one instrument and schema within `[start_ts, end_ts]`.
+#[derive(Debug, Serialize)]
+struct RecordWindow<'a> {
+    dataset: &'a Dataset,
+    instrument_id: i32,
+    start_ts: i64,
+    end_ts: i64,
+    schema: &'a Schema,
+}
+
+/// Coverage query of several symbols, sent as `symbols=AAPL,MSFT`.
+#[derive(Debug, Serialize)]
+struct CoverageQuery<'a> {
+    dataset: &'a Dataset,
+    #[serde(serialize_with = "serialize_symbols")]
+    symbols: &'a [String],
+    schema: &'a Schema,
+}
+
+/// `RetrieveParams` of a GET query, sent as `symbols=AAPL,MSFT&start_ts=..`.
+#[derive(Debug, Serialize)]
+struct RetrieveQuery<'a> {
+    #[serde(serialize_with = "serialize_symbols")]
+    symbols: &'a [String],
+    start_ts: i64,
+    end_ts: i64,
+    schema: &'a Schema,
+    dataset: &'a Dataset,
+    stype: &'a Stype,
+}
+
+impl<'a> From<&'a RetrieveParams> for RetrieveQuery<'a> {
+    fn from(params: &'a RetrieveParams) -> Self {
+        RetrieveQuery {
+            symbols: &params.symbols,
+            start_ts: params.start_ts,
+            end_ts: params.end_ts,
+            schema: &params.schema,
+            dataset: &params.dataset,
+            stype: &params.stype,
+        }
+    }
+}
+
+fn serialize_symbols<S>(symbols: &[String], serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&symbols.join(","))
+}
 
 #[derive(Clone)]
 pub struct Historical {
     base_url: String,
     client: Client,
+    max_query_bytes: Option<u64>,
 }
 
 impl Historical {
@@ -24,16 +113,19 @@ impl Historical {
         Historical {
             base_url: base_url.to_string(),
             client,
+            max_query_bytes: None,
         }
     }
 
+    /// Refuses `get_records` queries estimated above `max_bytes`, see `get_records_unchecked`
+    /// to bypass the limit for a single query.
+    pub fn with_max_query_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_query_bytes = Some(max_bytes);
+        self
+    }
+
     fn url(&self, endpoint: &str) -> String {
-        format!(
-            "{}{}{}",
-            self.base_url,
-            "/historical/".to_string(),
-            endpoint.to_string()
-        )
+        format!("{}{}{}", self.base_url, "/historical/", endpoint)
     }
 
     // Market data
@@ -47,48 +139,84 @@ impl Historical {
             return ApiResponse::<String>::from_response(response).await;
         }
 
-        // let api_response = ApiResponse::<String>::from_response(response).await?;
-        // Ok(api_response)
-        // Stream the server's response
-        let mut stream = response.bytes_stream();
-        // let mut last_response;
+        stream_response(response).await
+    }
 
-        // Output the streamed response directly to the user
-        while let Some(chunk) = stream.next().await {
-            match chunk {
-                Ok(bytes) => {
-                    let bytes_str = String::from_utf8_lossy(&bytes);
-                    match serde_json::from_str::<ApiResponse<String>>(&bytes_str) {
-                        Ok(response) => {
-                            println!("{:?}", response.message);
-                            if response.status != "success" {
-                                return Ok(response);
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Error while receiving chunk: {:?}", e);
-                            return Err(Error::from(e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error while reading chunk: {:?}", e);
-                    return Err(Error::from(e));
-                }
-            }
+    pub async fn create_mbp_from_file(&self, file_path: &str) -> Result<ApiResponse<String>> {
+        let url = self.url("mbp/create/bulk");
+        let response = self
+            .client
+            .post(&url)
+            .json(&file_path) // Ensure you send the file path correctly
+            .send()
+            .await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
         }
 
-        let api_response = ApiResponse::new("success", "", StatusCode::OK, "".to_string());
+        stream_response(response).await
+    }
+
+    /// Deletes the records of an instrument and schema within `[start_ts, end_ts]`.
+    ///
+    /// Returns data = ""
+    pub async fn delete_records(
+        &self,
+        dataset: &Dataset,
+        instrument_id: &i32,
+        start_ts: i64,
+        end_ts: i64,
+        schema: &Schema,
+    ) -> Result<ApiResponse<String>> {
+        let url = self.url("mbp/delete");
+        let window = RecordWindow {
+            dataset,
+            instrument_id: *instrument_id,
+            start_ts,
+            end_ts,
+            schema,
+        };
+        let response = self.client.delete(&url).json(&window).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
 
+        let api_response = ApiResponse::<String>::from_response(response).await?;
         Ok(api_response)
     }
 
-    pub async fn create_mbp_from_file(&self, file_path: &str) -> Result<ApiResponse<String>> {
-        let url = self.url("mbp/create/bulk");
+    /// Replaces the records of an instrument and schema within `[start_ts, end_ts]` with the
+    /// MBN encoded `data`. The delete and insert run in a single server transaction, so
+    /// readers see either the old or the new records, never a partially replaced range.
+    pub async fn replace_records(
+        &self,
+        dataset: &Dataset,
+        instrument_id: &i32,
+        start_ts: i64,
+        end_ts: i64,
+        schema: &Schema,
+        data: Vec<u8>,
+    ) -> Result<ApiResponse<String>> {
+        let url = self.url("mbp/replace");
+        let window = RecordWindow {
+            dataset,
+            instrument_id: *instrument_id,
+            start_ts,
+            end_ts,
+            schema,
+        };
         let response = self
             .client
             .post(&url)
-            .json(&file_path) // Ensure you send the file path correctly
+            .query(&window)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(data)
             .send()
             .await?;
 
@@ -98,40 +226,357 @@ impl Historical {
             return ApiResponse::<String>::from_response(response).await;
         }
 
-        // Stream the server's response
-        let mut stream = response.bytes_stream();
+        stream_response(response).await
+    }
 
-        // Output the streamed response directly to the user
-        while let Some(chunk) = stream.next().await {
-            match chunk {
-                Ok(bytes) => {
-                    let bytes_str = String::from_utf8_lossy(&bytes);
-                    match serde_json::from_str::<ApiResponse<String>>(&bytes_str) {
-                        Ok(response) => {
-                            println!("{:?}", response.message);
-                            if response.status != "success" {
-                                return Ok(response);
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Error while receiving chunk: {:?}", e);
-                            return Err(Error::from(e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error while reading chunk: {:?}", e);
-                    return Err(Error::from(e));
+    /// Uploads MBN encoded bytes read from `reader` as raw `application/octet-stream`
+    /// batches of at most `batch_size` bytes. Each batch is acknowledged by the server
+    /// before the next one is sent.
+    ///
+    /// Returns data = upload id, also on failure, so the upload can be continued with
+    /// `resume_mbp_upload`.
+    pub async fn create_mbp_batched<R>(
+        &self,
+        reader: R,
+        batch_size: usize,
+    ) -> Result<ApiResponse<String>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        check_batch_size(batch_size)?;
+
+        let url = self.url("mbp/upload/start");
+        let response = self.client.post(&url).send().await?;
+        let start = ApiResponse::<String>::from_response(response).await?;
+
+        if start.status != "success" {
+            return Ok(start);
+        }
+
+        self.upload_batches(&start.data, reader, batch_size, 0).await
+    }
+
+    /// Continues an interrupted batched upload from the batch following the last one
+    /// acknowledged by the server. `reader` must yield the same bytes as the original
+    /// upload, from the beginning, and `batch_size` must match the original value.
+    pub async fn resume_mbp_upload<R>(
+        &self,
+        upload_id: &str,
+        mut reader: R,
+        batch_size: usize,
+    ) -> Result<ApiResponse<String>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        check_batch_size(batch_size)?;
+
+        let status = self.upload_status(upload_id).await?;
+
+        if status.status != "success" {
+            return Ok(ApiResponse {
+                status: status.status,
+                message: status.message,
+                code: status.code,
+                data: upload_id.to_string(),
+            });
+        }
+
+        let next_batch = status.data.map(|batch| batch + 1).unwrap_or(0);
+
+        // Skip what the server already has
+        for _ in 0..next_batch {
+            read_batch(&mut reader, batch_size).await?;
+        }
+
+        self.upload_batches(upload_id, reader, batch_size, next_batch)
+            .await
+    }
+
+    /// Encodes `records` into MBN and uploads them in batches of at most `batch_size`
+    /// records, without holding the full dataset in memory. `batch_size` must be non-zero.
+    ///
+    /// Returns data = upload id, also on failure, so the upload can be continued with
+    /// `resume_mbp_from_records`.
+    pub async fn create_mbp_from_records<'a, I>(
+        &self,
+        metadata: &Metadata,
+        records: I,
+        batch_size: usize,
+    ) -> Result<ApiResponse<String>>
+    where
+        I: IntoIterator<Item = RecordRef<'a>>,
+    {
+        check_batch_size(batch_size)?;
+
+        let url = self.url("mbp/upload/start");
+        let response = self.client.post(&url).send().await?;
+        let start = ApiResponse::<String>::from_response(response).await?;
+
+        if start.status != "success" {
+            return Ok(start);
+        }
+
+        self.upload_record_batches(&start.data, metadata, records.into_iter(), batch_size, 0)
+            .await
+    }
+
+    /// Continues an interrupted `create_mbp_from_records` upload from the batch following the
+    /// last one acknowledged by the server. `records` must yield the same records as the
+    /// original upload, from the beginning, and `batch_size` must match the original value.
+    pub async fn resume_mbp_from_records<'a, I>(
+        &self,
+        upload_id: &str,
+        metadata: &Metadata,
+        records: I,
+        batch_size: usize,
+    ) -> Result<ApiResponse<String>>
+    where
+        I: IntoIterator<Item = RecordRef<'a>>,
+    {
+        check_batch_size(batch_size)?;
+
+        let status = self.upload_status(upload_id).await?;
+
+        if status.status != "success" {
+            return Ok(ApiResponse {
+                status: status.status,
+                message: status.message,
+                code: status.code,
+                data: upload_id.to_string(),
+            });
+        }
+
+        let next_batch = status.data.map(|batch| batch + 1).unwrap_or(0);
+
+        // Skip what the server already has
+        let mut records = records.into_iter();
+        let skipped = (next_batch as usize).saturating_mul(batch_size);
+        if skipped > 0 {
+            records.nth(skipped - 1);
+        }
+
+        self.upload_record_batches(upload_id, metadata, records, batch_size, next_batch)
+            .await
+    }
+
+    /// Returns data = index of the last batch acknowledged by the server, if any.
+    pub async fn upload_status(&self, upload_id: &str) -> Result<ApiResponse<Option<u64>>> {
+        let url = self.url(&format!("mbp/upload/status?upload_id={}", upload_id));
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Option<u64>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Option<u64>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    async fn upload_batches<R>(
+        &self,
+        upload_id: &str,
+        mut reader: R,
+        batch_size: usize,
+        first_batch: u64,
+    ) -> Result<ApiResponse<String>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut batch_index = first_batch;
+
+        loop {
+            let batch = read_batch(&mut reader, batch_size).await?;
+            if batch.is_empty() {
+                break;
+            }
+
+            let ack = self.upload_batch(upload_id, batch_index, batch).await?;
+            if ack.status != "success" {
+                return Ok(ack);
+            }
+            batch_index += 1;
+        }
+
+        self.finish_upload(upload_id).await
+    }
+
+    async fn upload_record_batches<'a, I>(
+        &self,
+        upload_id: &str,
+        metadata: &Metadata,
+        records: I,
+        batch_size: usize,
+        first_batch: u64,
+    ) -> Result<ApiResponse<String>>
+    where
+        I: Iterator<Item = RecordRef<'a>>,
+    {
+        let mut records = records.peekable();
+        let mut batch_index = first_batch;
+
+        while batch_index == 0 || records.peek().is_some() {
+            let chunk: Vec<RecordRef<'a>> = records.by_ref().take(batch_size).collect();
+
+            let mut bytes = Vec::new();
+            {
+                let mut encoder = CombinedEncoder::new(&mut bytes);
+                if batch_index == 0 {
+                    encoder.encode_metadata(metadata)?;
                 }
+                encoder.encode_records(&chunk)?;
+            }
+
+            let ack = self.upload_batch(upload_id, batch_index, bytes).await?;
+            if ack.status != "success" {
+                return Ok(ack);
             }
+            batch_index += 1;
+        }
+
+        self.finish_upload(upload_id).await
+    }
+
+    async fn upload_batch(
+        &self,
+        upload_id: &str,
+        batch_index: u64,
+        batch: Vec<u8>,
+    ) -> Result<ApiResponse<String>> {
+        let url = self.url(&format!(
+            "mbp/upload/batch?upload_id={}&batch={}",
+            upload_id, batch_index
+        ));
+        let response = self
+            .client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(batch)
+            .send()
+            .await?;
+
+        let ack = ApiResponse::<u64>::from_response(response).await?;
+
+        Ok(ApiResponse {
+            status: ack.status,
+            message: ack.message,
+            code: ack.code,
+            data: upload_id.to_string(),
+        })
+    }
+
+    async fn finish_upload(&self, upload_id: &str) -> Result<ApiResponse<String>> {
+        let url = self.url(&format!("mbp/upload/finish?upload_id={}", upload_id));
+        let response = self.client.post(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
+
+        let mut api_response = stream_response(response).await?;
+        api_response.data = upload_id.to_string();
+        Ok(api_response)
+    }
+
+    /// Returns data = estimated record count and byte size per symbol for the query.
+    pub async fn estimate(&self, params: &RetrieveParams) -> Result<ApiResponse<QueryEstimate>> {
+        let url = self.url("mbp/estimate");
+        let query = RetrieveQuery::from(params);
+        let response = self.client.get(&url).query(&query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<QueryEstimate>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<QueryEstimate>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Returns data = first and last timestamps and covered days per symbol.
+    pub async fn coverage(
+        &self,
+        dataset: &Dataset,
+        symbols: &[String],
+        schema: &Schema,
+    ) -> Result<ApiResponse<Vec<Coverage>>> {
+        let url = self.url("mbp/coverage");
+        let query = CoverageQuery {
+            dataset,
+            symbols,
+            schema,
+        };
+        let response = self.client.get(&url).query(&query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<Coverage>>::from_response(response).await;
         }
 
-        let api_response = ApiResponse::new("success", "", StatusCode::OK, "".to_string());
+        let api_response = ApiResponse::<Vec<Coverage>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Returns data = mapping intervals from each symbol to concrete instruments and raw
+    /// tickers over the query's time range, using the query's stype.
+    pub async fn resolve_symbols(
+        &self,
+        params: &RetrieveParams,
+    ) -> Result<ApiResponse<Vec<SymbolResolution>>> {
+        let url = self.url("symbology/resolve");
+        let query = RetrieveQuery::from(params);
+        let response = self.client.get(&url).query(&query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<SymbolResolution>>::from_response(response).await;
+        }
 
+        let api_response = ApiResponse::<Vec<SymbolResolution>>::from_response(response).await?;
         Ok(api_response)
     }
 
+    /// Retrieves records, refusing the query if it is estimated above the limit set with
+    /// `with_max_query_bytes`.
     pub async fn get_records(&self, params: &RetrieveParams) -> Result<ApiResponse<Vec<u8>>> {
+        if let Some(max_bytes) = self.max_query_bytes {
+            let estimate = self.estimate(params).await?;
+
+            if estimate.status != "success" {
+                return Ok(ApiResponse::with_default(
+                    &estimate.status,
+                    &estimate.message,
+                    estimate.code,
+                ));
+            }
+
+            if estimate.data.total_bytes > max_bytes {
+                return Ok(ApiResponse::new(
+                    "failed",
+                    &format!(
+                        "Query estimated at {} bytes ({} records) exceeds limit of {} bytes.",
+                        estimate.data.total_bytes, estimate.data.total_records, max_bytes
+                    ),
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    vec![],
+                ));
+            }
+        }
+
+        self.get_records_unchecked(params).await
+    }
+
+    /// Retrieves records without checking the query size limit.
+    pub async fn get_records_unchecked(
+        &self,
+        params: &RetrieveParams,
+    ) -> Result<ApiResponse<Vec<u8>>> {
         let url = self.url("mbp/get/stream");
         let response = self.client.get(&url).json(params).send().await?;
 
@@ -167,6 +612,10 @@ impl Historical {
     ) -> Result<()> {
         let response = self.get_records(params).await?;
 
+        if response.status != "success" {
+            return Err(Error::CustomError(response.message));
+        }
+
         // Create or open the file
         let mut file = File::create(file_path)?;
 
@@ -180,6 +629,62 @@ impl Historical {
     }
 }
 
+/// Reads the streamed status messages of a create request, returning early on failure.
+async fn stream_response(response: Response) -> Result<ApiResponse<String>> {
+    let mut stream = response.bytes_stream();
+
+    // Output the streamed response directly to the user
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(bytes) => {
+                let bytes_str = String::from_utf8_lossy(&bytes);
+                match serde_json::from_str::<ApiResponse<String>>(&bytes_str) {
+                    Ok(response) => {
+                        println!("{:?}", response.message);
+                        if response.status != "success" {
+                            return Ok(response);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error while receiving chunk: {:?}", e);
+                        return Err(Error::from(e));
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error while reading chunk: {:?}", e);
+                return Err(Error::from(e));
+            }
+        }
+    }
+
+    let api_response = ApiResponse::new("success", "", StatusCode::OK, "".to_string());
+
+    Ok(api_response)
+}
+
+fn check_batch_size(batch_size: usize) -> Result<()> {
+    if batch_size == 0 {
+        return Err(Error::CustomError(
+            "batch_size must be greater than zero.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reads up to `batch_size` bytes, returning an empty buffer once the reader is exhausted.
+async fn read_batch<R>(reader: &mut R, batch_size: usize) -> Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut batch = Vec::with_capacity(batch_size);
+    (&mut *reader)
+        .take(batch_size as u64)
+        .read_to_end(&mut batch)
+        .await?;
+    Ok(batch)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,12 +743,11 @@ mod tests {
         dotenv().ok();
         let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
         let client = Instruments::new(&base_url);
-        let _ = client.delete_symbol(&id).await?;
+        let _ = client.delete_symbol(id).await?;
 
         Ok(())
     }
 
-    #[allow(dead_code)]
     async fn create_dummy_records(ticker: &str, dataset: Dataset) -> anyhow::Result<i32> {
         dotenv().ok();
         let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
@@ -399,7 +903,7 @@ mod tests {
         assert_eq!(response.status, "success");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
@@ -487,83 +991,46 @@ mod tests {
         assert_eq!(response.status, "failed");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
 
     #[tokio::test]
-    #[serial]
-    // #[ignore]
-    async fn test_get_mbp() -> anyhow::Result<()> {
-        dotenv().ok();
-        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
-        let client = Historical::new(&base_url);
-
-        let ticker = "AAPL";
-        let dataset = Dataset::Equities;
-
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+    async fn test_read_batch() -> anyhow::Result<()> {
+        let mut reader = Cursor::new(vec![1u8, 2, 3, 4, 5]);
 
         // Test
-        let query_params = RetrieveParams {
-            symbols: vec!["AAPL".to_string()],
-            start_ts: 1704209103644092563,
-            end_ts: 1704239109644092565,
-            schema: Schema::Mbp1,
-            dataset,
-            stype: mbinary::enums::Stype::Raw,
-        };
-
-        let response = client.get_records(&query_params).await?;
-
-        let data = response.data;
-        let cursor = Cursor::new(data);
-        let mut decoder = Decoder::new(cursor)?;
-        let _decoded = decoder.decode().expect("Error decoding metadata.");
+        let first = read_batch(&mut reader, 2).await?;
+        let second = read_batch(&mut reader, 2).await?;
+        let third = read_batch(&mut reader, 2).await?;
+        let fourth = read_batch(&mut reader, 2).await?;
 
         // Validate
-        assert_eq!(response.code, 200);
-        assert_eq!(response.status, "success");
-
-        // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        assert_eq!(first, vec![1, 2]);
+        assert_eq!(second, vec![3, 4]);
+        assert_eq!(third, vec![5]);
+        assert!(fourth.is_empty());
 
         Ok(())
     }
 
     #[tokio::test]
-    #[serial]
-    // #[ignore]
-    async fn test_get_records_to_file() -> anyhow::Result<()> {
-        dotenv().ok();
-        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
-        let client = Historical::new(&base_url);
-
-        let ticker = "AAPL";
-        let dataset = Dataset::Equities;
-
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+    async fn test_zero_batch_size() -> anyhow::Result<()> {
+        let client = Historical::new("http://127.0.0.1:1");
+        let metadata = Metadata::new(Schema::Mbp1, Dataset::Equities, 0, 0, SymbolMap::new());
 
         // Test
-        let query_params = RetrieveParams {
-            symbols: vec!["AAPL".to_string()],
-            start_ts: 1704209103644092563,
-            end_ts: 1704239109644092565,
-            schema: Schema::Mbp1,
-            dataset,
-            stype: mbinary::enums::Stype::Raw,
-        };
-
-        let response = client
-            .get_records_to_file(&query_params, "tests/test_data_pull.bin")
-            .await?;
+        let batched = client.create_mbp_batched(Cursor::new(vec![1u8]), 0).await;
+        let resumed = client
+            .resume_mbp_upload("upload", Cursor::new(vec![1u8]), 0)
+            .await;
+        let records = client.create_mbp_from_records(&metadata, vec![], 0).await;
 
         // Validate
-        assert_eq!(response, ());
-
-        // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        assert!(matches!(batched, Err(Error::CustomError(_))));
+        assert!(matches!(resumed, Err(Error::CustomError(_))));
+        assert!(matches!(records, Err(Error::CustomError(_))));
 
         Ok(())
     }
@@ -571,7 +1038,7 @@ mod tests {
     #[tokio::test]
     #[serial]
     // #[ignore]
-    async fn test_get_ohlcv() -> anyhow::Result<()> {
+    async fn test_create_mbp_batched() -> anyhow::Result<()> {
         dotenv().ok();
         let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
         let client = Historical::new(&base_url);
@@ -579,9 +1046,283 @@ mod tests {
         let ticker = "AAPL";
         let dataset = Dataset::Equities;
 
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+        let id = create_dummy_instrument(ticker, dataset).await?;
 
-        // Test
+        // Pull test data
+        let mbp_1 = Mbp1Msg {
+            hd: { RecordHeader::new::<Mbp1Msg>(id as u32, 1704209103644092564, 0) },
+            price: 6770,
+            size: 1,
+            action: 1,
+            side: 2,
+            depth: 0,
+            flags: 0,
+            ts_recv: 1704209103644092564,
+            ts_in_delta: 17493,
+            sequence: 739763,
+            discriminator: 0,
+            levels: [BidAskPair {
+                ask_px: 1,
+                bid_px: 1,
+                bid_sz: 2,
+                ask_sz: 2,
+                bid_ct: 10,
+                ask_ct: 20,
+            }],
+        };
+        let mbp_2 = Mbp1Msg {
+            hd: { RecordHeader::new::<Mbp1Msg>(id as u32, 1704239109644092565, 0) },
+            price: 6870,
+            size: 2,
+            action: 1,
+            side: 1,
+            depth: 0,
+            flags: 0,
+            ts_recv: 1704209103644092565,
+            ts_in_delta: 17493,
+            sequence: 739763,
+            discriminator: 0,
+            levels: [BidAskPair {
+                ask_px: 1,
+                bid_px: 1,
+                bid_sz: 2,
+                ask_sz: 2,
+                bid_ct: 10,
+                ask_ct: 20,
+            }],
+        };
+        let record_ref1: RecordRef = (&mbp_1).into();
+        let record_ref2: RecordRef = (&mbp_2).into();
+
+        let metadata = Metadata::new(
+            Schema::Mbp1,
+            Dataset::Equities,
+            1704209103644092564,
+            1704209103644092566,
+            SymbolMap::new(),
+        );
+
+        // Test
+        let response = client
+            .create_mbp_from_records(&metadata, vec![record_ref1, record_ref2], 1)
+            .await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_delete_records() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Test
+        let response = client
+            .delete_records(
+                &dataset,
+                &id,
+                1704209103644092564,
+                1704209103644092565,
+                &Schema::Mbp1,
+            )
+            .await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_replace_records() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Corrected data
+        let mbp_1 = Mbp1Msg {
+            hd: { RecordHeader::new::<Mbp1Msg>(id as u32, 1704209103644092564, 0) },
+            price: 6775,
+            size: 1,
+            action: Action::Trade as i8,
+            side: 2,
+            depth: 0,
+            flags: 0,
+            ts_recv: 1704209103644092564,
+            ts_in_delta: 17493,
+            sequence: 739763,
+            discriminator: 0,
+            levels: [BidAskPair {
+                ask_px: 1,
+                bid_px: 1,
+                bid_sz: 2,
+                ask_sz: 2,
+                bid_ct: 10,
+                ask_ct: 20,
+            }],
+        };
+        let record_ref1: RecordRef = (&mbp_1).into();
+
+        let metadata = Metadata::new(
+            Schema::Mbp1,
+            Dataset::Equities,
+            1704209103644092564,
+            1704209103644092566,
+            SymbolMap::new(),
+        );
+
+        let mut buffer = Vec::new();
+        let mut encoder = CombinedEncoder::new(&mut buffer);
+        encoder.encode_metadata(&metadata)?;
+        encoder
+            .encode_records(&[record_ref1])
+            .expect("Encoding failed");
+
+        // Test
+        let response = client
+            .replace_records(
+                &dataset,
+                &id,
+                1704209103644092564,
+                1704209103644092565,
+                &Schema::Mbp1,
+                buffer,
+            )
+            .await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_mbp() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_instrument(ticker, dataset).await?;
+
+        // Test
+        let query_params = RetrieveParams {
+            symbols: vec!["AAPL".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704239109644092565,
+            schema: Schema::Mbp1,
+            dataset,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        let response = client.get_records(&query_params).await?;
+
+        let data = response.data;
+        let cursor = Cursor::new(data);
+        let mut decoder = Decoder::new(cursor)?;
+        let _decoded = decoder.decode().expect("Error decoding metadata.");
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_records_to_file() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Test
+        let query_params = RetrieveParams {
+            symbols: vec!["AAPL".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704239109644092565,
+            schema: Schema::Mbp1,
+            dataset,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        client
+            .get_records_to_file(&query_params, "tests/test_data_pull.bin")
+            .await?;
+
+        // Validate
+        let mut decoder = Decoder::<std::io::BufReader<std::fs::File>>::from_file(
+            "tests/test_data_pull.bin",
+        )?;
+        let records = decoder.decode().expect("Error decoding records.");
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|record| record.msg().header().instrument_id == id as u32));
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+        std::fs::remove_file("tests/test_data_pull.bin")?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_ohlcv() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_instrument(ticker, dataset).await?;
+
+        // Test
         let query_params = RetrieveParams {
             symbols: vec!["AAPL".to_string()],
             start_ts: 1704209103644092563,
@@ -603,7 +1344,7 @@ mod tests {
         assert_eq!(response.status, "success");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
@@ -619,7 +1360,7 @@ mod tests {
         let ticker = "AAPL";
         let dataset = Dataset::Equities;
 
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+        let id = create_dummy_instrument(ticker, dataset).await?;
 
         // Test
         let query_params = RetrieveParams {
@@ -643,7 +1384,7 @@ mod tests {
         assert_eq!(response.status, "success");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
@@ -658,7 +1399,7 @@ mod tests {
         let ticker = "AAPL";
         let dataset = Dataset::Equities;
 
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+        let id = create_dummy_instrument(ticker, dataset).await?;
 
         // Test
         let query_params = RetrieveParams {
@@ -682,7 +1423,7 @@ mod tests {
         assert_eq!(response.status, "success");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
@@ -697,7 +1438,7 @@ mod tests {
         let ticker = "AAPL";
         let dataset = Dataset::Equities;
 
-        let id = create_dummy_instrument(ticker, dataset.clone()).await?;
+        let id = create_dummy_instrument(ticker, dataset).await?;
 
         // Test
         let query_params = RetrieveParams {
@@ -721,7 +1462,186 @@ mod tests {
         assert_eq!(response.status, "success");
 
         // Cleanup
-        let _ = delete_dummy_instrument(&id).await?;
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retrieve_query_params() -> anyhow::Result<()> {
+        let params = RetrieveParams {
+            symbols: vec!["AAPL".to_string(), "MSFT".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704209903644092567,
+            schema: Schema::Mbp1,
+            dataset: Dataset::Equities,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        // Test
+        let encoded = serde_urlencoded::to_string(RetrieveQuery::from(&params))?;
+
+        // Validate
+        assert_eq!(
+            encoded,
+            "symbols=AAPL%2CMSFT&start_ts=1704209103644092563&end_ts=1704209903644092567&schema=Mbp1&dataset=Equities&stype=Raw"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_estimate() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_instrument(ticker, dataset).await?;
+
+        // Test
+        let query_params = RetrieveParams {
+            symbols: vec!["AAPL".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704239109644092565,
+            schema: Schema::Mbp1,
+            dataset,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        let response = client.estimate(&query_params).await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert_eq!(response.data.symbols.len(), 1);
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_records_over_limit() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url).with_max_query_bytes(0);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Test
+        let query_params = RetrieveParams {
+            symbols: vec!["AAPL".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704239109644092565,
+            schema: Schema::Mbp1,
+            dataset,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        let response = client.get_records(&query_params).await?;
+        let unchecked = client.get_records_unchecked(&query_params).await?;
+
+        // Validate
+        assert_eq!(response.code, 413);
+        assert_eq!(response.status, "failed");
+        assert_eq!(unchecked.status, "success");
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coverage_query_params() -> anyhow::Result<()> {
+        let symbols = vec!["AAPL".to_string(), "MSFT".to_string()];
+        let query = CoverageQuery {
+            dataset: &Dataset::Equities,
+            symbols: &symbols,
+            schema: &Schema::Mbp1,
+        };
+
+        // Test
+        let encoded = serde_urlencoded::to_string(&query)?;
+
+        // Validate
+        assert_eq!(encoded, "dataset=Equities&symbols=AAPL%2CMSFT&schema=Mbp1");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_coverage() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Test
+        let response = client
+            .coverage(&dataset, &["AAPL".to_string()], &Schema::Mbp1)
+            .await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert_eq!(response.data[0].instrument_id, id as u32);
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_resolve_symbols() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Historical::new(&base_url);
+
+        let ticker = "AAPL";
+        let dataset = Dataset::Equities;
+
+        let id = create_dummy_records(ticker, dataset).await?;
+
+        // Test
+        let query_params = RetrieveParams {
+            symbols: vec!["AAPL".to_string()],
+            start_ts: 1704209103644092563,
+            end_ts: 1704239109644092565,
+            schema: Schema::Mbp1,
+            dataset,
+            stype: mbinary::enums::Stype::Raw,
+        };
+
+        let response = client.resolve_symbols(&query_params).await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert_eq!(response.data[0].intervals[0].instrument_id, id as u32);
+
+        // Cleanup
+        delete_dummy_instrument(&id).await?;
 
         Ok(())
     }
@@ -747,7 +1667,7 @@ mod tests {
             mbinary::enums::Stype::Continuous,
         )?;
 
-        let _response = client.get_records_to_file(&query_params, "bbo.bin").await?;
+        client.get_records_to_file(&query_params, "bbo.bin").await?;
 
         Ok(())
     }
@@ -1,12 +1,331 @@
-use crate::error::Result;
+use crate::contracts::{build_chains, ContractChain};
+use crate::error::{Error, Result};
 use crate::response::ApiResponse;
+use crate::views::{InstrumentView, VendorFilter};
 use mbinary::enums::Dataset;
 use mbinary::symbols::Instrument;
 use mbinary::vendors::Vendors;
 use reqwest::{self, Client, ClientBuilder};
 use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
 use std::time::Duration;
 
+/// Page size used when paging through all instruments matching a query.
+pub const DEFAULT_PAGE_SIZE: u32 = 1_000;
+
+/// Filters for `Instruments::query`, encoded as query parameters.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InstrumentQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticker_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dataset: Option<Dataset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vendor: Option<Vendors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_end: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_ids"
+    )]
+    ids: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+}
+
+impl InstrumentQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ticker(mut self, ticker: &str) -> Self {
+        self.ticker = Some(ticker.to_string());
+        self
+    }
+
+    pub fn ticker_prefix(mut self, prefix: &str) -> Self {
+        self.ticker_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn dataset(mut self, dataset: Dataset) -> Self {
+        self.dataset = Some(dataset);
+        self
+    }
+
+    pub fn vendor(mut self, vendor: Vendors) -> Self {
+        self.vendor = Some(vendor);
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+        self
+    }
+
+    /// Instruments with an expiration date within `[start, end]`, in unix nanoseconds.
+    pub fn expiring_between(mut self, start: u64, end: u64) -> Self {
+        self.expiration_start = Some(start);
+        self.expiration_end = Some(end);
+        self
+    }
+
+    pub fn ids(mut self, ids: &[u32]) -> Self {
+        self.ids = ids.to_vec();
+        self
+    }
+
+    pub fn page(mut self, offset: u32, limit: u32) -> Self {
+        self.offset = Some(offset);
+        self.limit = Some(limit);
+        self
+    }
+}
+
+fn serialize_ids<S>(ids: &[u32], serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let joined = ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+    serializer.serialize_str(&joined)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpsertStatus {
+    Created,
+    Updated,
+    Unchanged,
+    Deactivated,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub current: String,
+    pub proposed: String,
+}
+
+/// Outcome of upserting a single instrument, `changes` lists the fields that differ from
+/// what the server held.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpsertResult {
+    pub ticker: String,
+    pub instrument_id: Option<u32>,
+    pub status: UpsertStatus,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+/// State of an instrument after a change, as recorded in its audit history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentVersion {
+    pub version: u32,
+    pub changed_at: i64,
+    pub change: ChangeKind,
+    pub instrument: Instrument,
+}
+
+/// Ticker changes within `history`, as `(changed_at, previous, current)`.
+pub fn ticker_renames(history: &[InstrumentVersion]) -> Vec<(i64, String, String)> {
+    let mut versions: Vec<&InstrumentVersion> = history.iter().collect();
+    versions.sort_by_key(|version| version.version);
+
+    versions
+        .windows(2)
+        .filter(|pair| pair[0].instrument.ticker != pair[1].instrument.ticker)
+        .map(|pair| {
+            (
+                pair[1].changed_at,
+                pair[0].instrument.ticker.clone(),
+                pair[1].instrument.ticker.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Compares `proposed` instruments against `current` ones, matched on ticker and dataset.
+pub fn diff_instruments(current: &[Instrument], proposed: &[Instrument]) -> Vec<UpsertResult> {
+    proposed
+        .iter()
+        .map(|instrument| {
+            let existing = current
+                .iter()
+                .find(|c| c.ticker == instrument.ticker && c.dataset == instrument.dataset);
+
+            match existing {
+                None => UpsertResult {
+                    ticker: instrument.ticker.clone(),
+                    instrument_id: None,
+                    status: UpsertStatus::Created,
+                    message: String::new(),
+                    changes: vec![],
+                },
+                Some(existing) => {
+                    let changes = field_changes(existing, instrument);
+                    let status = if changes.is_empty() {
+                        UpsertStatus::Unchanged
+                    } else {
+                        UpsertStatus::Updated
+                    };
+
+                    UpsertResult {
+                        ticker: instrument.ticker.clone(),
+                        instrument_id: existing.instrument_id,
+                        status,
+                        message: String::new(),
+                        changes,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Active `current` instruments missing from `proposed`, which a sync deactivates.
+/// Continuous instruments are never part of a definition file and are left alone.
+pub fn deactivations(current: &[Instrument], proposed: &[Instrument]) -> Vec<UpsertResult> {
+    current
+        .iter()
+        .filter(|instrument| instrument.active && !instrument.is_continuous)
+        .filter(|instrument| {
+            !proposed
+                .iter()
+                .any(|p| p.ticker == instrument.ticker && p.dataset == instrument.dataset)
+        })
+        .map(|instrument| UpsertResult {
+            ticker: instrument.ticker.clone(),
+            instrument_id: instrument.instrument_id,
+            status: UpsertStatus::Deactivated,
+            message: String::new(),
+            changes: vec![FieldChange {
+                field: "active".to_string(),
+                current: true.to_string(),
+                proposed: false.to_string(),
+            }],
+        })
+        .collect()
+}
+
+fn field_changes(current: &Instrument, proposed: &Instrument) -> Vec<FieldChange> {
+    let fields = [
+        ("name", current.name.clone(), proposed.name.clone()),
+        (
+            "vendor",
+            format!("{:?}", current.vendor),
+            format!("{:?}", proposed.vendor),
+        ),
+        (
+            "vendor_data",
+            current.vendor_data.to_string(),
+            proposed.vendor_data.to_string(),
+        ),
+        (
+            "first_available",
+            current.first_available.to_string(),
+            proposed.first_available.to_string(),
+        ),
+        (
+            "last_available",
+            current.last_available.to_string(),
+            proposed.last_available.to_string(),
+        ),
+        (
+            "expiration_date",
+            current.expiration_date.to_string(),
+            proposed.expiration_date.to_string(),
+        ),
+        (
+            "is_continuous",
+            current.is_continuous.to_string(),
+            proposed.is_continuous.to_string(),
+        ),
+        (
+            "active",
+            current.active.to_string(),
+            proposed.active.to_string(),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, current, proposed)| current != proposed)
+        .map(|(field, current, proposed)| FieldChange {
+            field: field.to_string(),
+            current,
+            proposed,
+        })
+        .collect()
+}
+
+fn into_views(
+    response: ApiResponse<Vec<Instrument>>,
+    filter: &VendorFilter,
+) -> Result<ApiResponse<Vec<InstrumentView>>> {
+    let mut views = Vec::new();
+    for instrument in response.data {
+        let view = InstrumentView::try_from(instrument)?;
+        if filter.matches(&view) {
+            views.push(view);
+        }
+    }
+
+    Ok(ApiResponse {
+        status: response.status,
+        message: response.message,
+        code: response.code,
+        data: views,
+    })
+}
+
+/// Reads instrument definitions from a JSON array.
+pub fn load_instruments_json<P: AsRef<Path>>(path: P) -> Result<Vec<Instrument>> {
+    let file = File::open(path)?;
+    let instruments: Vec<Instrument> = serde_json::from_reader(BufReader::new(file))?;
+    Ok(instruments)
+}
+
+/// Reads instrument definitions from a CSV file with a header row named after the
+/// `Instrument` fields.
+pub fn load_instruments_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Instrument>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut instruments = Vec::new();
+
+    for row in reader.deserialize() {
+        let instrument: Instrument = row?;
+        instruments.push(instrument);
+    }
+
+    Ok(instruments)
+}
+
 #[derive(Clone)]
 pub struct Instruments {
     base_url: String,
@@ -27,12 +346,7 @@ impl Instruments {
     }
 
     fn url(&self, endpoint: &str) -> String {
-        format!(
-            "{}{}{}",
-            self.base_url,
-            "/instruments/".to_string(),
-            endpoint.to_string()
-        )
+        format!("{}{}{}", self.base_url, "/instruments/", endpoint)
     }
 
     // Instruments
@@ -52,31 +366,188 @@ impl Instruments {
         Ok(api_response)
     }
 
+    /// Returns data = instruments matching every filter set on `query`, for the page
+    /// requested with `InstrumentQuery::page` if any.
+    pub async fn query(&self, query: &InstrumentQuery) -> Result<ApiResponse<Vec<Instrument>>> {
+        let url = self.url("query");
+        let response = self.client.get(&url).query(query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<Instrument>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<Instrument>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Pages through `query` with pages of `page_size` instruments until exhausted.
+    pub async fn query_all(
+        &self,
+        query: &InstrumentQuery,
+        page_size: u32,
+    ) -> Result<ApiResponse<Vec<Instrument>>> {
+        if page_size == 0 {
+            return Err(Error::CustomError(
+                "page_size must be greater than zero.".to_string(),
+            ));
+        }
+
+        let mut instruments = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let page = query.clone().page(offset, page_size);
+            let response = self.query(&page).await?;
+
+            if response.status != "success" {
+                return Ok(response);
+            }
+
+            let count = response.data.len() as u32;
+            instruments.extend(response.data);
+
+            if count < page_size {
+                break;
+            }
+            offset += count;
+        }
+
+        Ok(ApiResponse::new("success", "", StatusCode::OK, instruments))
+    }
+
+    /// Returns data = the instrument, None when no instrument has the id.
+    pub async fn get_by_id(&self, id: &i32) -> Result<ApiResponse<Option<Instrument>>> {
+        let url = self.url(&format!("get_by_id?id={}", id));
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Option<Instrument>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<Instrument>>::from_response(response).await?;
+        Ok(ApiResponse {
+            status: api_response.status,
+            message: api_response.message,
+            code: api_response.code,
+            data: api_response.data.into_iter().next(),
+        })
+    }
+
     pub async fn get_symbol(
         &self,
-        ticker: &String,
+        ticker: &str,
         dataset: &Dataset,
     ) -> Result<ApiResponse<Vec<Instrument>>> {
-        let url = self.url("get");
-
-        // Send GET request
-        let response = self
-            .client
-            .get(&url)
-            .json(&(ticker, dataset))
-            .send()
-            .await?;
+        let query = InstrumentQuery::new().ticker(ticker).dataset(*dataset);
+        self.query(&query).await
+    }
+
+    /// Creates or updates each instrument, matched on ticker and dataset, reporting the
+    /// outcome per instrument. With `dry_run` nothing is written and the results show the
+    /// diff against the instruments currently held by the server.
+    pub async fn bulk_upsert(
+        &self,
+        instruments: &[Instrument],
+        dry_run: bool,
+    ) -> Result<ApiResponse<Vec<UpsertResult>>> {
+        if dry_run {
+            let mut datasets: Vec<&Dataset> = Vec::new();
+            for instrument in instruments {
+                if !datasets.contains(&&instrument.dataset) {
+                    datasets.push(&instrument.dataset);
+                }
+            }
+
+            let mut current = Vec::new();
+            for dataset in datasets {
+                let response = self.list_dataset_symbols(dataset).await?;
+
+                if response.status != "success" {
+                    return Ok(ApiResponse::with_default(
+                        &response.status,
+                        &response.message,
+                        response.code,
+                    ));
+                }
+                current.extend(response.data);
+            }
+
+            let results = diff_instruments(&current, instruments);
+            return Ok(ApiResponse::new("success", "", StatusCode::OK, results));
+        }
+
+        let url = self.url("bulk_upsert");
+        let response = self.client.post(&url).json(instruments).send().await?;
 
         // Check for HTTP status
         if response.status() != StatusCode::OK {
             // Deserialize the API response and return it, even if it indicates failure
-            return ApiResponse::<Vec<Instrument>>::from_response(response).await;
+            return ApiResponse::<Vec<UpsertResult>>::from_response(response).await;
         }
 
-        let api_response = ApiResponse::<Vec<Instrument>>::from_response(response).await?;
+        let api_response = ApiResponse::<Vec<UpsertResult>>::from_response(response).await?;
         Ok(api_response)
     }
 
+    /// Reconciles the instruments of `dataset` with `instruments`, creating and updating
+    /// as `bulk_upsert` does and deactivating the server instruments missing from
+    /// `instruments`. With `dry_run` nothing is written.
+    pub async fn sync_dataset(
+        &self,
+        dataset: &Dataset,
+        instruments: &[Instrument],
+        dry_run: bool,
+    ) -> Result<ApiResponse<Vec<UpsertResult>>> {
+        let current = self.list_dataset_symbols(dataset).await?;
+
+        if current.status != "success" {
+            return Ok(ApiResponse::with_default(
+                &current.status,
+                &current.message,
+                current.code,
+            ));
+        }
+
+        let stale = deactivations(&current.data, instruments);
+
+        if dry_run {
+            let mut results = diff_instruments(&current.data, instruments);
+            results.extend(stale);
+            return Ok(ApiResponse::new("success", "", StatusCode::OK, results));
+        }
+
+        let upserted = self.bulk_upsert(instruments, false).await?;
+        if upserted.status != "success" {
+            return Ok(upserted);
+        }
+
+        let mut results = upserted.data;
+        for mut result in stale {
+            let existing = current
+                .data
+                .iter()
+                .find(|instrument| instrument.instrument_id == result.instrument_id);
+
+            if let Some(existing) = existing {
+                let mut instrument = existing.clone();
+                instrument.active = false;
+
+                let response = self.update_symbol(&instrument).await?;
+                if response.status != "success" {
+                    result.status = UpsertStatus::Failed;
+                    result.message = response.message;
+                }
+            }
+            results.push(result);
+        }
+
+        Ok(ApiResponse::new("success", "", StatusCode::OK, results))
+    }
+
     /// Returns data = ""
     pub async fn delete_symbol(&self, id: &i32) -> Result<ApiResponse<String>> {
         let url = self.url("delete");
@@ -92,6 +563,53 @@ impl Instruments {
         Ok(api_response)
     }
 
+    /// Marks the instrument as deleted while keeping it and its records, see `restore_symbol`.
+    ///
+    /// Returns data = ""
+    pub async fn soft_delete_symbol(&self, id: &i32) -> Result<ApiResponse<String>> {
+        let url = self.url("soft_delete");
+        let response = self.client.put(&url).json(id).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<String>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Returns data = ""
+    pub async fn restore_symbol(&self, id: &i32) -> Result<ApiResponse<String>> {
+        let url = self.url("restore");
+        let response = self.client.put(&url).json(id).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<String>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Returns data = previous versions of the instrument, oldest first.
+    pub async fn symbol_history(&self, id: &i32) -> Result<ApiResponse<Vec<InstrumentVersion>>> {
+        let url = self.url(&format!("history?id={}", id));
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<InstrumentVersion>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<InstrumentVersion>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
     pub async fn update_symbol(
         &self,
         instrument: &Instrument,
@@ -126,27 +644,60 @@ impl Instruments {
         Ok(api_response)
     }
 
-    pub async fn list_vendor_symbols(
+    /// Same as `get_symbol` with the vendor data of each instrument decoded, fails on
+    /// instruments whose vendor data doesn't decode.
+    pub async fn get_symbol_views(
+        &self,
+        ticker: &str,
+        dataset: &Dataset,
+    ) -> Result<ApiResponse<Vec<InstrumentView>>> {
+        let response = self.get_symbol(ticker, dataset).await?;
+        into_views(response, &VendorFilter::new())
+    }
+
+    /// Same as `list_vendor_symbols` with the vendor data of each instrument decoded and
+    /// filtered on vendor-specific fields, e.g. `GLBX.MDP3` instruments ingested as `mbp-1`.
+    pub async fn list_vendor_views(
         &self,
         vendor: &Vendors,
         dataset: &Dataset,
-    ) -> Result<ApiResponse<Vec<Instrument>>> {
-        let url = self.url("list_vendor");
-        let response = self
-            .client
-            .get(&url)
-            .json(&(vendor, dataset))
-            .send()
-            .await?;
+        filter: &VendorFilter,
+    ) -> Result<ApiResponse<Vec<InstrumentView>>> {
+        let response = self.list_vendor_symbols(vendor, dataset).await?;
+        into_views(response, filter)
+    }
 
-        // Check for HTTP status
-        if response.status() != StatusCode::OK {
-            // Deserialize the API response and return it, even if it indicates failure
-            return ApiResponse::<Vec<Instrument>>::from_response(response).await;
+    /// Returns data = raw futures contracts of the dataset grouped into chains per root symbol.
+    pub async fn contract_chains(
+        &self,
+        dataset: &Dataset,
+    ) -> Result<ApiResponse<Vec<ContractChain>>> {
+        let response = self.list_dataset_symbols(dataset).await?;
+
+        if response.status != "success" {
+            return Ok(ApiResponse::with_default(
+                &response.status,
+                &response.message,
+                response.code,
+            ));
         }
 
-        let api_response = ApiResponse::<Vec<Instrument>>::from_response(response).await?;
-        Ok(api_response)
+        let chains = build_chains(&response.data);
+        Ok(ApiResponse::new(
+            &response.status,
+            &response.message,
+            StatusCode::OK,
+            chains,
+        ))
+    }
+
+    pub async fn list_vendor_symbols(
+        &self,
+        vendor: &Vendors,
+        dataset: &Dataset,
+    ) -> Result<ApiResponse<Vec<Instrument>>> {
+        let query = InstrumentQuery::new().vendor(*vendor).dataset(*dataset);
+        self.query_all(&query, DEFAULT_PAGE_SIZE).await
     }
 }
 #[cfg(test)]
@@ -154,6 +705,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::definitions::load_definitions;
     use dotenv::dotenv;
     use mbinary::enums::Dataset;
     use mbinary::symbols::Instrument;
@@ -291,37 +843,411 @@ mod tests {
         let client = Instruments::new(&base_url);
         let id = create_dummy_instrument(&client).await?;
 
+        // Test
+        let response = client.get_symbol("AAPL", &Dataset::Equities).await?;
+
+        println!("{:?}", response);
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert!(!response.data.is_empty());
+
+        // Cleanup
+        let _ = client.delete_symbol(&id).await?;
+
+        Ok(())
+    }
+
+    fn dummy_instrument(ticker: &str, name: &str) -> Instrument {
+        Instrument::new(
+            None,
+            ticker,
+            name,
+            Dataset::Futures,
+            Vendors::Databento,
+            1,
+            1,
+            1,
+            1,
+            false,
+            true,
+        )
+    }
+
+    #[test]
+    fn test_diff_instruments() {
+        let current = vec![
+            dummy_instrument("HEG4", "Lean hogs"),
+            dummy_instrument("ZCH4", "Corn"),
+        ];
+        let proposed = vec![
+            dummy_instrument("HEG4", "Lean hogs"),
+            dummy_instrument("ZCH4", "Corn futures"),
+            dummy_instrument("ZSH4", "Soybeans"),
+        ];
+
+        // Test
+        let results = diff_instruments(&current, &proposed);
+
+        // Validate
+        assert_eq!(results[0].status, UpsertStatus::Unchanged);
+        assert_eq!(results[1].status, UpsertStatus::Updated);
+        assert_eq!(
+            results[1].changes,
+            vec![FieldChange {
+                field: "name".to_string(),
+                current: "Corn".to_string(),
+                proposed: "Corn futures".to_string(),
+            }]
+        );
+        assert_eq!(results[2].status, UpsertStatus::Created);
+    }
+
+    #[test]
+    fn test_ticker_renames() {
+        let history = vec![
+            InstrumentVersion {
+                version: 2,
+                changed_at: 200,
+                change: ChangeKind::Updated,
+                instrument: dummy_instrument("HEG24", "Lean hogs"),
+            },
+            InstrumentVersion {
+                version: 1,
+                changed_at: 100,
+                change: ChangeKind::Created,
+                instrument: dummy_instrument("HEG4", "Lean hogs"),
+            },
+            InstrumentVersion {
+                version: 3,
+                changed_at: 300,
+                change: ChangeKind::Updated,
+                instrument: dummy_instrument("HEG24", "Lean hogs futures"),
+            },
+        ];
+
+        // Test
+        let renames = ticker_renames(&history);
+
+        // Validate
+        assert_eq!(
+            renames,
+            vec![(200, "HEG4".to_string(), "HEG24".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_deactivations() {
+        let mut continuous = dummy_instrument("HE.c.0", "Lean hogs");
+        continuous.is_continuous = true;
+        let current = vec![
+            dummy_instrument("HEG4", "Lean hogs"),
+            dummy_instrument("ZCH4", "Corn"),
+            continuous,
+        ];
+        let proposed = vec![dummy_instrument("HEG4", "Lean hogs")];
+
+        // Test
+        let results = deactivations(&current, &proposed);
+
+        // Validate
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].ticker, "ZCH4");
+        assert_eq!(results[0].status, UpsertStatus::Deactivated);
+    }
+
+    #[test]
+    fn test_load_instruments_json() -> anyhow::Result<()> {
+        let instruments = vec![dummy_instrument("HEG4", "Lean hogs")];
+        let path = std::env::temp_dir().join("midas_client_instruments.json");
+        std::fs::write(&path, serde_json::to_string(&instruments)?)?;
+
+        // Test
+        let loaded = load_instruments_json(&path)?;
+
+        // Validate
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].ticker, "HEG4");
+
+        // Cleanup
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_instruments_csv() -> anyhow::Result<()> {
+        // Test
+        let loaded = load_instruments_csv("tests/data/test_data.instruments.csv")?;
+
+        // Validate
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].ticker, "HEG4");
+        assert_eq!(loaded[0].instrument_id, None);
+        assert_eq!(loaded[0].dataset, Dataset::Futures);
+        assert_eq!(loaded[0].vendor, Vendors::Databento);
+        assert_eq!(loaded[0].expiration_date, 1707955200000000000);
+        assert_eq!(loaded[1].name, "Apple Inc.");
+        assert_eq!(loaded[1].instrument_id, Some(42));
+        assert_eq!(loaded[1].vendor, Vendors::Yfinance);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_instruments_csv_malformed_row() {
+        // Test
+        let result = load_instruments_csv("tests/data/test_data.instruments_malformed.csv");
+
+        // Validate
+        match result {
+            Err(Error::CsvError(e)) => assert_eq!(e.position().map(|p| p.line()), Some(3)),
+            other => panic!("Expected a CSV error, got {:?}", other.map(|i| i.len())),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_sync_dataset_dry_run() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+        let instruments = load_definitions(
+            "tests/data/test_data.definitions.dbn",
+            Dataset::Futures,
+            dbn::Schema::Mbp1,
+            0,
+        )?;
+
         // Test
         let response = client
-            .get_symbol(&"AAPL".to_string(), &Dataset::Equities)
+            .sync_dataset(&Dataset::Futures, &instruments, true)
             .await?;
 
-        println!("{:?}", response);
+        // Validate
+        assert_eq!(response.status, "success");
+        for ticker in ["HEG4", "HEJ4"] {
+            let result = response
+                .data
+                .iter()
+                .find(|result| result.ticker == ticker)
+                .expect("Expected a result per definition");
+            assert_ne!(result.status, UpsertStatus::Deactivated);
+        }
+        assert!(response
+            .data
+            .iter()
+            .filter(|result| result.status == UpsertStatus::Deactivated)
+            .all(|result| !instruments.iter().any(|i| i.ticker == result.ticker)));
+
+        // Dry run writes nothing
+        let listed = client.list_dataset_symbols(&Dataset::Futures).await?;
+        for result in response
+            .data
+            .iter()
+            .filter(|result| result.status == UpsertStatus::Created)
+        {
+            assert!(!listed
+                .data
+                .iter()
+                .any(|instrument| instrument.ticker == result.ticker));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_bulk_upsert() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+        let id = create_dummy_instrument(&client).await?;
+
+        let schema = dbn::Schema::from_str("mbp-1")?;
+        let dbn_dataset = dbn::Dataset::from_str("GLBX.MDP3")?;
+        let stype = dbn::SType::from_str("raw_symbol")?;
+        let vendor_data = VendorData::Databento(DatabentoData {
+            schema,
+            dataset: dbn_dataset,
+            stype,
+        });
+
+        let instruments = vec![
+            Instrument::new(
+                None,
+                "AAPL",
+                "Apple tester client",
+                Dataset::Equities,
+                Vendors::Databento,
+                vendor_data.encode(),
+                1,
+                1,
+                1,
+                false,
+                true,
+            ),
+            Instrument::new(
+                None,
+                "AAP00002",
+                "Apple tester client",
+                Dataset::Equities,
+                Vendors::Databento,
+                vendor_data.encode(),
+                1,
+                1,
+                1,
+                false,
+                true,
+            ),
+        ];
+
+        // Test
+        let dry_run = client.bulk_upsert(&instruments, true).await?;
+        let response = client.bulk_upsert(&instruments, false).await?;
 
         // Validate
+        assert_eq!(dry_run.status, "success");
+        assert_eq!(dry_run.data[0].status, UpsertStatus::Unchanged);
+        assert_eq!(dry_run.data[1].status, UpsertStatus::Created);
         assert_eq!(response.code, 200);
         assert_eq!(response.status, "success");
-        assert!(response.data.len() > 0);
+        assert_eq!(response.data[1].status, UpsertStatus::Created);
 
         // Cleanup
         let _ = client.delete_symbol(&id).await?;
+        if let Some(created) = response.data[1].instrument_id {
+            let _ = client.delete_symbol(&(created as i32)).await?;
+        }
 
         Ok(())
     }
 
+    #[test]
+    fn test_instrument_query_params() -> anyhow::Result<()> {
+        let query = InstrumentQuery::new()
+            .ticker_prefix("HE")
+            .active(true)
+            .ids(&[1, 2, 3])
+            .page(0, 100);
+
+        // Test
+        let encoded = serde_urlencoded::to_string(&query)?;
+
+        // Validate
+        assert_eq!(
+            encoded,
+            "ticker_prefix=HE&active=true&ids=1%2C2%2C3&limit=100&offset=0"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_all_zero_page_size() {
+        let client = Instruments::new("http://127.0.0.1:1");
+
+        // Test
+        let response = client.query_all(&InstrumentQuery::new(), 0).await;
+
+        // Validate
+        assert!(matches!(response, Err(Error::CustomError(_))));
+    }
+
     #[tokio::test]
     #[serial]
     // #[ignore]
-    async fn test_get_instrument_none() -> anyhow::Result<()> {
+    async fn test_query_instruments() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+        let id = create_dummy_instrument(&client).await?;
+
+        // Test
+        let query = InstrumentQuery::new()
+            .ticker("AAPL")
+            .dataset(Dataset::Equities)
+            .vendor(Vendors::Databento);
+        let response = client.query_all(&query, 1).await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].instrument_id, Some(id as u32));
+
+        // Cleanup
+        let _ = client.delete_symbol(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_by_id() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+        let id = create_dummy_instrument(&client).await?;
+
+        // Test
+        let response = client.get_by_id(&id).await?;
+        let _ = client.delete_symbol(&id).await?;
+        let deleted = client.get_by_id(&id).await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert_eq!(response.data.unwrap().ticker, "AAPL");
+        assert!(deleted.data.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_list_vendor_views() -> anyhow::Result<()> {
         dotenv().ok();
         let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
         let client = Instruments::new(&base_url);
+        let id = create_dummy_instrument(&client).await?;
 
         // Test
+        let filter = VendorFilter::new().dataset("GLBX.MDP3").schema("mbp-1");
         let response = client
-            .get_symbol(&("AAPL9".to_string()), &Dataset::Equities)
+            .list_vendor_views(&Vendors::Databento, &Dataset::Equities, &filter)
             .await?;
 
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+        assert!(response
+            .data
+            .iter()
+            .any(|view| view.instrument.instrument_id == Some(id as u32)));
+
+        // Cleanup
+        let _ = client.delete_symbol(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_instrument_none() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+
+        // Test
+        let response = client.get_symbol("AAPL9", &Dataset::Equities).await?;
+
         // Validate
         assert_eq!(response.code, 404); // Request was valid but that ticker doesnt exist
         assert_eq!(response.status, "success");
@@ -374,6 +1300,43 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_soft_delete_and_restore() -> anyhow::Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Instruments::new(&base_url);
+        let id = create_dummy_instrument(&client).await?;
+
+        // Test
+        let deleted = client.soft_delete_symbol(&id).await?;
+        let restored = client.restore_symbol(&id).await?;
+        let history = client.symbol_history(&id).await?;
+
+        // Validate
+        assert_eq!(deleted.status, "success");
+        assert_eq!(restored.status, "success");
+        assert_eq!(history.code, 200);
+        assert_eq!(
+            history
+                .data
+                .iter()
+                .map(|version| version.change)
+                .collect::<Vec<ChangeKind>>(),
+            vec![
+                ChangeKind::Created,
+                ChangeKind::Deleted,
+                ChangeKind::Restored
+            ]
+        );
+
+        // Cleanup
+        let _ = client.delete_symbol(&id).await?;
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     // #[ignore]
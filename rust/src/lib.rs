@@ -1,9 +1,26 @@
 // pub mod client;
+pub mod bundle;
+pub mod cache;
+pub mod compare;
+pub mod contracts;
+pub mod coverage;
+pub mod definitions;
+pub mod drift;
 pub mod error;
+pub mod export;
 pub mod historical;
 pub mod instrument;
+pub mod report;
 pub mod response;
+pub mod retention;
+pub mod session;
+pub mod stats;
+pub mod summary;
+pub mod symbology;
 pub mod trading;
+pub mod upload;
 pub mod utils;
+pub mod views;
+pub mod watch;
 
 pub use self::error::{Error, Result};
@@ -0,0 +1,455 @@
+use crate::error::Result;
+use crate::export::{self, PRICE_SCALE};
+use crate::stats::{display_scale, StatsConfig};
+use chrono::{Datelike, TimeZone, Utc};
+use mbinary::backtest::{BacktestData, TimeseriesStats};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 200.0;
+
+/// Fixed-point scales of the values shown in a report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportConfig {
+    /// Scale of prices, trade values, fees and equity.
+    pub price_scale: f64,
+    /// Scales of the static stats and drawdowns.
+    pub stats: StatsConfig,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        ReportConfig {
+            price_scale: PRICE_SCALE,
+            stats: StatsConfig::default(),
+        }
+    }
+}
+
+/// Return of each calendar month from the daily equity, keyed by `(year, month)`.
+pub fn monthly_returns(daily: &[TimeseriesStats]) -> BTreeMap<(i32, u32), f64> {
+    let mut month_end: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+    for stat in daily {
+        let date = Utc.timestamp_nanos(stat.timestamp).date_naive();
+        month_end.insert((date.year(), date.month()), stat.equity_value as f64);
+    }
+
+    let mut returns = BTreeMap::new();
+    let mut previous = daily.first().map(|stat| stat.equity_value as f64);
+
+    for (month, equity) in month_end {
+        if let Some(start) = previous {
+            if start != 0.0 {
+                returns.insert(month, equity / start - 1.0);
+            }
+        }
+        previous = Some(equity);
+    }
+
+    returns
+}
+
+/// Self-contained HTML tearsheet with inline SVG charts, no external assets.
+pub fn render_html(backtest: &BacktestData, config: &ReportConfig) -> String {
+    let name = escape(&backtest.metadata.backtest_name);
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", name));
+    html.push_str(
+        "<style>\
+         body{font-family:sans-serif;margin:2em;}\
+         table{border-collapse:collapse;margin-bottom:2em;}\
+         th,td{border:1px solid #ccc;padding:4px 8px;text-align:right;}\
+         th{background:#f4f4f4;}\
+         </style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", name));
+
+    html.push_str("<h2>Equity</h2>\n");
+    html.push_str(&svg_line_chart(
+        &series(&backtest.period_timeseries_stats, |stat| {
+            stat.equity_value as f64 / config.price_scale
+        }),
+        "#1f77b4",
+    ));
+    html.push_str("<h2>Drawdown</h2>\n");
+    html.push_str(&svg_line_chart(
+        &series(&backtest.period_timeseries_stats, |stat| {
+            stat.percent_drawdown as f64 / config.stats.ratio_scale
+        }),
+        "#d62728",
+    ));
+    html.push_str("<h2>Daily equity</h2>\n");
+    html.push_str(&svg_line_chart(
+        &series(&backtest.daily_timeseries_stats, |stat| {
+            stat.equity_value as f64 / config.price_scale
+        }),
+        "#2ca02c",
+    ));
+
+    html.push_str("<h2>Static stats</h2>\n");
+    html.push_str(&html_table(
+        &["Stat", "Value"],
+        &stat_rows(backtest, config),
+    ));
+
+    html.push_str("<h2>Monthly returns</h2>\n");
+    html.push_str(&monthly_heatmap(&monthly_returns(
+        &backtest.daily_timeseries_stats,
+    )));
+
+    html.push_str("<h2>Trades</h2>\n");
+    html.push_str(&html_table(&TRADE_HEADER, &trade_rows(backtest, config)));
+
+    html.push_str("<h2>Signals</h2>\n");
+    html.push_str(&html_table(&SIGNAL_HEADER, &signal_rows(backtest, config)));
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Markdown variant of the tearsheet, tables only.
+pub fn render_markdown(backtest: &BacktestData, config: &ReportConfig) -> String {
+    let mut markdown = format!("# {}\n\n", backtest.metadata.backtest_name);
+
+    markdown.push_str("## Static stats\n\n");
+    markdown.push_str(&markdown_table(
+        &["Stat", "Value"],
+        &stat_rows(backtest, config),
+    ));
+
+    markdown.push_str("## Monthly returns\n\n");
+    let monthly: Vec<Vec<String>> = monthly_returns(&backtest.daily_timeseries_stats)
+        .into_iter()
+        .map(|((year, month), value)| {
+            vec![
+                format!("{}-{:02}", year, month),
+                format!("{:.2}%", value * 100.0),
+            ]
+        })
+        .collect();
+    markdown.push_str(&markdown_table(&["Month", "Return"], &monthly));
+
+    markdown.push_str("## Trades\n\n");
+    markdown.push_str(&markdown_table(
+        &TRADE_HEADER,
+        &trade_rows(backtest, config),
+    ));
+
+    markdown.push_str("## Signals\n\n");
+    markdown.push_str(&markdown_table(
+        &SIGNAL_HEADER,
+        &signal_rows(backtest, config),
+    ));
+
+    markdown
+}
+
+pub fn write_html<P: AsRef<Path>>(
+    backtest: &BacktestData,
+    path: P,
+    config: &ReportConfig,
+) -> Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(render_html(backtest, config).as_bytes())?;
+    Ok(())
+}
+
+const TRADE_HEADER: [&str; 9] = [
+    "Trade",
+    "Signal",
+    "Timestamp",
+    "Ticker",
+    "Action",
+    "Quantity",
+    "Avg price",
+    "Value",
+    "Fees",
+];
+
+const SIGNAL_HEADER: [&str; 9] = [
+    "Timestamp",
+    "Signal",
+    "Ticker",
+    "Order type",
+    "Action",
+    "Weight",
+    "Quantity",
+    "Limit price",
+    "Aux price",
+];
+
+fn series<F>(stats: &[TimeseriesStats], value: F) -> Vec<f64>
+where
+    F: Fn(&TimeseriesStats) -> f64,
+{
+    stats.iter().map(value).collect()
+}
+
+fn stat_rows(backtest: &BacktestData, config: &ReportConfig) -> Vec<Vec<String>> {
+    match serde_json::to_value(&backtest.metadata.static_stats) {
+        Ok(Value::Object(map)) => map
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value.as_f64() {
+                    Some(value) => {
+                        let scale = display_scale(&name, &config.stats, config.price_scale);
+                        (value / scale).to_string()
+                    }
+                    None => value.to_string(),
+                };
+                vec![name, value]
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn trade_rows(backtest: &BacktestData, config: &ReportConfig) -> Vec<Vec<String>> {
+    export::trade_rows(&backtest.trades, config.price_scale, None)
+        .into_iter()
+        .map(|trade| {
+            vec![
+                trade.trade_id.to_string(),
+                trade.signal_id.to_string(),
+                trade.timestamp.to_string(),
+                trade.ticker,
+                trade.action,
+                trade.quantity.to_string(),
+                trade.avg_price.to_string(),
+                trade.trade_value.to_string(),
+                trade.fees.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn signal_rows(backtest: &BacktestData, config: &ReportConfig) -> Vec<Vec<String>> {
+    let optional = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+
+    export::signal_rows(&backtest.signals, config.price_scale, None)
+        .into_iter()
+        .map(|signal| {
+            vec![
+                signal.timestamp.to_string(),
+                signal.signal_id.to_string(),
+                signal.ticker,
+                signal.order_type,
+                signal.action,
+                signal.weight.to_string(),
+                signal.quantity.to_string(),
+                optional(signal.limit_price),
+                optional(signal.aux_price),
+            ]
+        })
+        .collect()
+}
+
+fn svg_line_chart(values: &[f64], color: &str) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#fafafa\" stroke=\"#ccc\"/>\n",
+        CHART_WIDTH, CHART_HEIGHT
+    ));
+
+    if !values.is_empty() {
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let range = if max > min { max - min } else { 1.0 };
+        let step = if values.len() > 1 {
+            CHART_WIDTH / (values.len() - 1) as f64
+        } else {
+            0.0
+        };
+
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let x = i as f64 * step;
+                let y = CHART_HEIGHT - (value - min) / range * CHART_HEIGHT;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect();
+
+        svg.push_str(&format!(
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>\n",
+            color,
+            points.join(" ")
+        ));
+        svg.push_str(&format!(
+            "<text x=\"4\" y=\"14\" font-size=\"12\">{}</text>\n<text x=\"4\" y=\"{}\" font-size=\"12\">{}</text>\n",
+            max,
+            CHART_HEIGHT - 4.0,
+            min
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn monthly_heatmap(returns: &BTreeMap<(i32, u32), f64>) -> String {
+    let mut years: Vec<i32> = returns.keys().map(|(year, _)| *year).collect();
+    years.dedup();
+
+    let mut html = String::from("<table>\n<tr><th>Year</th>");
+    for month in 1..=12 {
+        html.push_str(&format!("<th>{:02}</th>", month));
+    }
+    html.push_str("</tr>\n");
+
+    for year in years {
+        html.push_str(&format!("<tr><th>{}</th>", year));
+        for month in 1..=12 {
+            match returns.get(&(year, month)) {
+                Some(value) => {
+                    // Green for gains, red for losses, stronger for larger moves
+                    let alpha = (value.abs() * 10.0).min(1.0);
+                    let color = if *value >= 0.0 {
+                        format!("rgba(44,160,44,{:.2})", alpha)
+                    } else {
+                        format!("rgba(214,39,40,{:.2})", alpha)
+                    };
+                    html.push_str(&format!(
+                        "<td style=\"background:{}\">{:.2}%</td>",
+                        color,
+                        value * 100.0
+                    ));
+                }
+                None => html.push_str("<td></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn html_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for title in header {
+        html.push_str(&format!("<th>{}</th>", escape(title)));
+    }
+    html.push_str("</tr>\n");
+
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn markdown_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut markdown = format!("| {} |\n", header.join(" | "));
+    markdown.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| markdown_escape(cell)).collect();
+        markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    markdown.push('\n');
+    markdown
+}
+
+/// Escapes pipes and line breaks, which would otherwise split or end a table row.
+pub(crate) fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::date_to_unix_nanos;
+    use std::fs;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    #[test]
+    fn test_monthly_returns() -> Result<()> {
+        let mut daily = load_backtest().daily_timeseries_stats;
+        daily.truncate(1);
+        let mut stat = daily[0].clone();
+
+        daily[0].timestamp = date_to_unix_nanos("2024-01-02")?;
+        daily[0].equity_value = 100;
+        stat.timestamp = date_to_unix_nanos("2024-01-31")?;
+        stat.equity_value = 110;
+        daily.push(stat.clone());
+        stat.timestamp = date_to_unix_nanos("2024-02-29")?;
+        stat.equity_value = 99;
+        daily.push(stat);
+
+        // Test
+        let returns = monthly_returns(&daily);
+
+        // Validate
+        assert_eq!(returns.len(), 2);
+        assert!((returns[&(2024, 1)] - 0.10).abs() < 1e-9);
+        assert!((returns[&(2024, 2)] + 0.10).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_html() {
+        let backtest = load_backtest();
+
+        // Test
+        let html = render_html(&backtest, &ReportConfig::default());
+
+        // Validate
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>testing76543</title>"));
+        assert_eq!(html.matches("<polyline").count(), 3);
+        assert!(html.contains("<td>sharpe_ratio</td><td>6.771709</td>"));
+        assert!(html.contains("<td>AAPL</td><td>BUY</td>"));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut backtest = load_backtest();
+        backtest.trades[0].ticker = "ES|NQ".to_string();
+        let config = ReportConfig {
+            price_scale: 100.0,
+            ..Default::default()
+        };
+
+        // Test
+        let markdown = render_markdown(&backtest, &config);
+
+        // Validate
+        assert!(markdown.starts_with("# testing76543"));
+        assert!(markdown.contains("| sharpe_ratio | 6.771709 |"));
+        assert!(markdown.contains("| MSFT | LMT | SELL |"));
+        assert!(markdown.contains("| ES\\|NQ | BUY | 4 | 130.74 |"));
+    }
+}
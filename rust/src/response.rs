@@ -15,6 +15,11 @@ impl ApiDefault for u32 {
         0
     }
 }
+impl ApiDefault for u64 {
+    fn default_value() -> Self {
+        0
+    }
+}
 
 impl ApiDefault for String {
     fn default_value() -> Self {
@@ -41,9 +46,9 @@ pub struct RawApiResponse {
     pub code: u16,
 }
 
-impl<T: serde::de::DeserializeOwned + ApiDefault> Into<ApiResponse<T>> for RawApiResponse {
-    fn into(self) -> ApiResponse<T> {
-        ApiResponse::with_default(&self.status, &self.message, self.code)
+impl<T: serde::de::DeserializeOwned + ApiDefault> From<RawApiResponse> for ApiResponse<T> {
+    fn from(raw: RawApiResponse) -> Self {
+        ApiResponse::with_default(&raw.status, &raw.message, raw.code)
     }
 }
 
@@ -0,0 +1,203 @@
+use crate::error::{Error, Result};
+use crate::summary::BacktestSummary;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Stat backtests are ranked by when keeping the best ones, higher is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankBy {
+    NetProfit,
+    TotalReturn,
+    SharpeRatio,
+    SortinoRatio,
+}
+
+impl RankBy {
+    fn value(&self, summary: &BacktestSummary) -> i64 {
+        match self {
+            RankBy::NetProfit => summary.net_profit,
+            RankBy::TotalReturn => summary.total_return,
+            RankBy::SharpeRatio => summary.sharpe_ratio,
+            RankBy::SortinoRatio => summary.sortino_ratio,
+        }
+    }
+}
+
+/// Decides which backtests to delete. A backtest is deleted when it is older than the max age
+/// and outside the top N of its strategy, without a max age nothing is expired.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    keep_top: Option<(usize, RankBy)>,
+    max_age: Option<Duration>,
+}
+
+/// Backtest ids split by the retention policy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPlan {
+    pub keep: Vec<i32>,
+    pub delete: Vec<i32>,
+}
+
+impl RetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always keep the best `count` backtests of each strategy.
+    pub fn keep_top(mut self, count: usize, rank_by: RankBy) -> Self {
+        self.keep_top = Some((count, rank_by));
+        self
+    }
+
+    /// Only delete backtests created more than `max_age` ago.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Splits the backtests, `now` and `created_at` are unix nanoseconds. Errors when no rule is
+    /// set.
+    pub fn plan(&self, backtests: &[BacktestSummary], now: i64) -> Result<RetentionPlan> {
+        if self.keep_top.is_none() && self.max_age.is_none() {
+            return Err(Error::CustomError(
+                "Retention policy has no rules, set keep_top or max_age.".to_string(),
+            ));
+        }
+
+        let mut top: HashSet<i32> = HashSet::new();
+
+        if let Some((count, rank_by)) = self.keep_top {
+            let mut by_strategy: HashMap<&str, Vec<&BacktestSummary>> = HashMap::new();
+            for backtest in backtests {
+                by_strategy
+                    .entry(backtest.strategy_name.as_str())
+                    .or_default()
+                    .push(backtest);
+            }
+
+            for mut strategy in by_strategy.into_values() {
+                strategy.sort_by_key(|backtest| std::cmp::Reverse(rank_by.value(backtest)));
+                top.extend(strategy.iter().take(count).map(|backtest| backtest.id));
+            }
+        }
+
+        let cutoff = self
+            .max_age
+            .map(|max_age| now.saturating_sub(max_age.as_nanos() as i64));
+
+        let mut plan = RetentionPlan::default();
+        for backtest in backtests {
+            let expired = match cutoff {
+                Some(cutoff) => backtest.created_at < cutoff,
+                None => false,
+            };
+
+            if top.contains(&backtest.id) || !expired {
+                plan.keep.push(backtest.id);
+            } else {
+                plan.delete.push(backtest.id);
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    const DAY: i64 = 86_400_000_000_000;
+    // 2024-01-10 15:30:00 UTC, as the server stores created_at
+    const NOW: i64 = 1_704_900_600_000_000_000;
+
+    fn summary(id: i32, strategy_name: &str, sharpe_ratio: i64, age_days: i64) -> BacktestSummary {
+        BacktestSummary {
+            id,
+            backtest_name: format!("sweep_{}", id),
+            strategy_name: strategy_name.to_string(),
+            created_at: NOW - age_days * DAY,
+            tickers: vec!["AAPL".to_string()],
+            start: 0,
+            end: 0,
+            total_trades: 0,
+            net_profit: 0,
+            total_return: 0,
+            sharpe_ratio,
+            sortino_ratio: 0,
+            max_drawdown_percentage_period: 0,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_keep_top_and_max_age() -> Result<()> {
+        let backtests = vec![
+            summary(1, "cointegrationzscore", 3, 40),
+            summary(2, "cointegrationzscore", 2, 40),
+            summary(3, "cointegrationzscore", 1, 40),
+            summary(4, "cointegrationzscore", 0, 5),
+            summary(5, "meanreversion", -1, 40),
+        ];
+        let policy = RetentionPolicy::new()
+            .keep_top(1, RankBy::SharpeRatio)
+            .max_age(Duration::from_secs(30 * 86_400));
+
+        // Test
+        let plan = policy.plan(&backtests, NOW)?;
+
+        // Validate
+        assert_eq!(plan.keep, vec![1, 4, 5]);
+        assert_eq!(plan.delete, vec![2, 3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_age_only() -> Result<()> {
+        let backtests = vec![
+            summary(1, "cointegrationzscore", 3, 40),
+            summary(2, "cointegrationzscore", 2, 5),
+        ];
+        let policy = RetentionPolicy::new().max_age(Duration::from_secs(30 * 86_400));
+
+        // Test
+        let plan = policy.plan(&backtests, NOW)?;
+
+        // Validate
+        assert_eq!(plan.keep, vec![2]);
+        assert_eq!(plan.delete, vec![1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_top_only() -> Result<()> {
+        let backtests = vec![
+            summary(1, "cointegrationzscore", 3, 40),
+            summary(2, "cointegrationzscore", 2, 40),
+        ];
+        let policy = RetentionPolicy::new().keep_top(1, RankBy::SharpeRatio);
+
+        // Test
+        let plan = policy.plan(&backtests, NOW)?;
+
+        // Validate
+        assert_eq!(plan.keep, vec![1, 2]);
+        assert!(plan.delete.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_rules() {
+        let backtests = vec![summary(1, "cointegrationzscore", 3, 40)];
+
+        // Test
+        let plan = RetentionPolicy::new().plan(&backtests, NOW);
+
+        // Validate
+        assert!(matches!(plan, Err(Error::CustomError(_))));
+    }
+}
@@ -0,0 +1,342 @@
+use crate::error::{Error, Result};
+use crate::response::ApiResponse;
+use crate::trading::Trading;
+use mbinary::backtest::{Parameters, Signals, Trades};
+use mbinary::live::AccountSummary;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SESSION_BATCH_SIZE: usize = 100;
+
+/// Updates sent to a live session in a single `Trading::append_live` call. `sequence`
+/// numbers the chunks a `LiveSession` sends, a resent chunk keeps its number so the server
+/// can drop it when the first attempt did arrive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiveUpdate {
+    pub trades: Vec<Trades>,
+    pub signals: Vec<Signals>,
+    pub account: Option<AccountSummary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+}
+
+impl LiveUpdate {
+    pub fn len(&self) -> usize {
+        self.trades.len() + self.signals.len() + usize::from(self.account.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// First `size` updates, trades then signals, with the account once both fit.
+    fn head(&self, size: usize) -> LiveUpdate {
+        let trades = self.trades.len().min(size);
+        let signals = self.signals.len().min(size - trades);
+        let account = if trades + signals < size {
+            self.account.clone()
+        } else {
+            None
+        };
+
+        LiveUpdate {
+            trades: self.trades[..trades].to_vec(),
+            signals: self.signals[..signals].to_vec(),
+            account,
+            sequence: None,
+        }
+    }
+
+    /// Removes the updates of `head` from the buffer.
+    fn drain_head(&mut self, head: &LiveUpdate) {
+        self.trades.drain(..head.trades.len());
+        self.signals.drain(..head.signals.len());
+        if head.account.is_some() {
+            self.account = None;
+        }
+    }
+}
+
+/// Handle to a running live session. Updates are buffered locally and sent in batches, when
+/// the server can't be reached they stay buffered until the next flush. Call `finalize` on
+/// shutdown so nothing buffered is lost.
+pub struct LiveSession {
+    trading: Trading,
+    id: i32,
+    batch_size: usize,
+    sequence: u64,
+    in_flight: Option<LiveUpdate>,
+    pending: LiveUpdate,
+}
+
+impl LiveSession {
+    /// Opens a new session on the server.
+    pub async fn start(trading: &Trading, parameters: &Parameters) -> Result<Self> {
+        let response = trading.start_live(parameters).await?;
+
+        if response.status != "success" {
+            return Err(Error::CustomError(format!(
+                "Failed to start live session: {}",
+                response.message
+            )));
+        }
+
+        Ok(Self::resume(trading, response.data))
+    }
+
+    /// Attaches to a session that is already open, e.g. after a restart.
+    pub fn resume(trading: &Trading, id: i32) -> Self {
+        LiveSession {
+            trading: trading.clone(),
+            id,
+            batch_size: DEFAULT_SESSION_BATCH_SIZE,
+            sequence: 0,
+            in_flight: None,
+            pending: LiveUpdate::default(),
+        }
+    }
+
+    /// Continues numbering chunks at `sequence`, e.g. the `sequence()` saved before a restart.
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Number of updates sent per `append_live` call, and buffered before `is_full` is true.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Sequence number of the next chunk to send.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn push_trade(&mut self, trade: Trades) {
+        self.pending.trades.push(trade);
+    }
+
+    pub fn push_signal(&mut self, signal: Signals) {
+        self.pending.signals.push(signal);
+    }
+
+    /// Only the latest account snapshot is kept until the next flush.
+    pub fn update_account(&mut self, account: AccountSummary) {
+        self.pending.account = Some(account);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.in_flight.as_ref().map_or(0, LiveUpdate::len) + self.pending.len()
+    }
+
+    /// True once a batch worth of updates is buffered and should be flushed.
+    pub fn is_full(&self) -> bool {
+        self.pending() >= self.batch_size
+    }
+
+    /// Drops the chunk at the head of the buffer and returns it, e.g. after the server
+    /// rejected it outright so it would fail every flush.
+    pub fn discard_head(&mut self) -> Option<LiveUpdate> {
+        let head = self.in_flight.take()?;
+        self.sequence += 1;
+        Some(head)
+    }
+
+    /// Sends the buffered updates in chunks of `batch_size`. Each chunk is removed from the
+    /// buffer only once the server accepted it, when the server is unreachable or rejects a
+    /// chunk it and everything after it are kept for the next flush. A chunk that was sent
+    /// once is resent as is with the same sequence number, updates pushed in the meantime
+    /// go into later chunks.
+    pub async fn flush(&mut self) -> Result<ApiResponse<String>> {
+        let mut response = ApiResponse::new(
+            "success",
+            "Nothing to flush.",
+            StatusCode::OK,
+            "".to_string(),
+        );
+
+        while self.pending() > 0 {
+            let chunk = match self.in_flight.take() {
+                Some(chunk) => chunk,
+                None => {
+                    let mut chunk = self.pending.head(self.batch_size);
+                    self.pending.drain_head(&chunk);
+                    chunk.sequence = Some(self.sequence);
+                    chunk
+                }
+            };
+            let sent = self.trading.append_live(&self.id, &chunk).await;
+            self.in_flight = Some(chunk);
+
+            response = match sent {
+                Ok(response) => response,
+                Err(Error::RequestError(e)) => {
+                    return Ok(ApiResponse::new(
+                        "failed",
+                        &format!(
+                            "Server unreachable, {} updates buffered: {}",
+                            self.pending(),
+                            e
+                        ),
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "".to_string(),
+                    ))
+                }
+                Err(e) => return Err(e),
+            };
+
+            if response.status != "success" {
+                return Ok(response);
+            }
+            self.in_flight = None;
+            self.sequence += 1;
+        }
+
+        Ok(response)
+    }
+
+    /// Flushes the remaining updates and closes the session. The session is only finalized
+    /// once everything buffered was sent, otherwise the failed flush response is returned.
+    pub async fn finalize(&mut self) -> Result<ApiResponse<String>> {
+        let response = self.flush().await?;
+
+        if response.status != "success" {
+            return Ok(response);
+        }
+
+        self.trading.finalize_live(&self.id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dotenv::dotenv;
+    use mbinary::live::LiveData;
+    use serial_test::serial;
+    use std::fs;
+
+    fn load_live() -> LiveData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    #[test]
+    fn test_live_update_head() {
+        let live_data = load_live();
+        let mut update = LiveUpdate {
+            trades: vec![live_data.trades[0].clone()],
+            signals: vec![live_data.signals[0].clone(), live_data.signals[0].clone()],
+            account: Some(live_data.account.clone()),
+            sequence: None,
+        };
+
+        // Test
+        let first = update.head(2);
+        update.drain_head(&first);
+        let second = update.head(2);
+        update.drain_head(&second);
+
+        // Validate
+        assert_eq!(first.trades.len(), 1);
+        assert_eq!(first.signals.len(), 1);
+        assert!(first.account.is_none());
+        assert_eq!(second.trades.len(), 0);
+        assert_eq!(second.signals.len(), 1);
+        assert!(second.account.is_some());
+        assert!(update.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_buffer_when_unreachable() -> Result<()> {
+        let live_data = load_live();
+        let trading = Trading::new("http://127.0.0.1:1");
+        let mut session = LiveSession::resume(&trading, 1).with_batch_size(2);
+
+        session.push_trade(live_data.trades[0].clone());
+        session.push_signal(live_data.signals[0].clone());
+        session.update_account(live_data.account.clone());
+
+        // Test
+        let response = session.flush().await?;
+
+        // Validate
+        assert!(session.is_full());
+        assert_eq!(response.status, "failed");
+        assert_eq!(response.code, 503);
+        assert_eq!(session.pending(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_flush_resends_and_discards_head() -> Result<()> {
+        let live_data = load_live();
+        let trading = Trading::new("http://127.0.0.1:1");
+        let mut session = LiveSession::resume(&trading, 1)
+            .with_batch_size(2)
+            .with_sequence(7);
+
+        session.push_trade(live_data.trades[0].clone());
+        let _ = session.flush().await?;
+        session.push_signal(live_data.signals[0].clone());
+
+        // Test
+        let response = session.flush().await?;
+        let head = session.discard_head();
+
+        // Validate
+        assert_eq!(response.code, 503);
+        let head = head.expect("Expected a chunk at the head.");
+        assert_eq!(head.trades.len(), 1);
+        assert!(head.signals.is_empty());
+        assert_eq!(head.sequence, Some(7));
+        assert_eq!(session.sequence(), 8);
+        assert_eq!(session.pending(), 1);
+        assert!(session.discard_head().is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_live_session() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let trading = Trading::new(&base_url);
+        let live_data = load_live();
+
+        let mut session = LiveSession::start(&trading, &live_data.parameters).await?;
+
+        // Test
+        for trade in live_data.trades.iter() {
+            session.push_trade(trade.clone());
+        }
+        let flushed = session.flush().await?;
+        for signal in live_data.signals.iter() {
+            session.push_signal(signal.clone());
+        }
+        session.update_account(live_data.account.clone());
+        let finalized = session.finalize().await?;
+
+        // Validate
+        assert_eq!(flushed.status, "success");
+        assert_eq!(finalized.status, "success");
+        assert_eq!(session.pending(), 0);
+
+        let response = trading.get_live(&session.id()).await?;
+        assert_eq!(response.data[0].trades.len(), live_data.trades.len());
+
+        // Cleanup
+        let _ = trading.delete_live(&session.id()).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,551 @@
+use crate::error::Result;
+use mbinary::backtest::{BacktestData, StaticStats, TimeseriesStats, Trades};
+use mbinary::live::LiveData;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+const NANOS_PER_DAY: f64 = 86_400_000_000_000.0;
+
+/// Static stats holding money amounts, compared after applying `StatsConfig::money_scale`.
+const MONEY_STATS: [&str; 7] = [
+    "avg_profit",
+    "avg_gain",
+    "avg_loss",
+    "total_fees",
+    "net_profit",
+    "beginning_equity",
+    "ending_equity",
+];
+
+/// Static stats holding counts, compared as is.
+const COUNT_STATS: [&str; 3] = [
+    "total_trades",
+    "total_winning_trades",
+    "total_losing_trades",
+];
+
+/// How the fixed-point `static_stats` are scaled and how closely they have to match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatsConfig {
+    /// Scale of the ratio and percentage stats, e.g. `sharpe_ratio` or `total_return`.
+    pub ratio_scale: f64,
+    /// Scale of the money stats relative to the trade and timeseries values.
+    pub money_scale: f64,
+    /// Daily returns per year, used to annualize volatility, Sharpe and Sortino.
+    pub periods_per_year: f64,
+    /// Annual risk free rate as a fraction.
+    pub risk_free_rate: f64,
+    /// Allowed relative difference between the reported and the recomputed value.
+    pub tolerance: f64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        StatsConfig {
+            ratio_scale: 1_000_000.0,
+            money_scale: 1.0,
+            periods_per_year: 252.0,
+            risk_free_rate: 0.0,
+            tolerance: 0.01,
+        }
+    }
+}
+
+/// Stats derived from the trades alone, shared by backtests and live sessions.
+/// Gains and losses are realized per ticker on an average cost basis, before fees.
+/// `total_trades` counts closed round trips rather than fills, like the backtest engine,
+/// so it always equals `total_winning_trades + total_losing_trades`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TradeStats {
+    pub total_trades: f64,
+    pub total_winning_trades: f64,
+    pub total_losing_trades: f64,
+    pub avg_profit: f64,
+    pub avg_profit_percent: f64,
+    pub avg_gain: f64,
+    pub avg_gain_percent: f64,
+    pub avg_loss: f64,
+    pub avg_loss_percent: f64,
+    pub profitability_ratio: f64,
+    pub profit_factor: f64,
+    pub profit_and_loss_ratio: f64,
+    pub total_fees: f64,
+}
+
+/// Stats derived from an equity curve, returns and drawdowns are fractions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EquityStats {
+    pub net_profit: f64,
+    pub beginning_equity: f64,
+    pub ending_equity: f64,
+    pub total_return: f64,
+    pub annualized_return: f64,
+    pub daily_standard_deviation_percentage: f64,
+    pub annual_standard_deviation_percentage: f64,
+    pub max_drawdown_percentage_period: f64,
+    pub max_drawdown_percentage_daily: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+}
+
+/// Recomputed counterpart of the backtest `static_stats`, in natural units.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComputedStats {
+    #[serde(flatten)]
+    pub trades: TradeStats,
+    #[serde(flatten)]
+    pub equity: EquityStats,
+}
+
+/// Stat whose reported value doesn't match the recomputed one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Discrepancy {
+    pub name: String,
+    pub reported: f64,
+    pub computed: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClosedTrade {
+    pnl: f64,
+    return_pct: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Position {
+    quantity: f64,
+    avg_cost: f64,
+}
+
+pub fn trade_stats(trades: &[Trades]) -> TradeStats {
+    let closed = close_trades(trades);
+    let gains: Vec<&ClosedTrade> = closed.iter().filter(|trade| trade.pnl > 0.0).collect();
+    let losses: Vec<&ClosedTrade> = closed.iter().filter(|trade| trade.pnl <= 0.0).collect();
+
+    let gross_gain: f64 = gains.iter().map(|trade| trade.pnl).sum();
+    let gross_loss: f64 = losses.iter().map(|trade| trade.pnl).sum();
+    let avg_gain = mean(gains.iter().map(|trade| trade.pnl));
+    let avg_loss = mean(losses.iter().map(|trade| trade.pnl));
+
+    TradeStats {
+        total_trades: closed.len() as f64,
+        total_winning_trades: gains.len() as f64,
+        total_losing_trades: losses.len() as f64,
+        avg_profit: mean(closed.iter().map(|trade| trade.pnl)),
+        avg_profit_percent: mean(closed.iter().map(|trade| trade.return_pct)),
+        avg_gain,
+        avg_gain_percent: mean(gains.iter().map(|trade| trade.return_pct)),
+        avg_loss,
+        avg_loss_percent: mean(losses.iter().map(|trade| trade.return_pct)),
+        profitability_ratio: ratio(gains.len() as f64, closed.len() as f64),
+        profit_factor: ratio(gross_gain, gross_loss.abs()),
+        profit_and_loss_ratio: ratio(avg_gain, avg_loss.abs()),
+        total_fees: -trades.iter().map(|trade| trade.fees as f64).sum::<f64>(),
+    }
+}
+
+/// Equity stats of the period and daily timeseries, timestamps are unix nanoseconds.
+pub fn equity_stats(
+    period: &[TimeseriesStats],
+    daily: &[TimeseriesStats],
+    config: &StatsConfig,
+) -> EquityStats {
+    let period_equity: Vec<f64> = period.iter().map(|stat| stat.equity_value as f64).collect();
+    let daily_equity: Vec<f64> = daily.iter().map(|stat| stat.equity_value as f64).collect();
+
+    let beginning_equity = period_equity.first().copied().unwrap_or(0.0);
+    let ending_equity = period_equity.last().copied().unwrap_or(0.0);
+    let total_return = ratio(ending_equity - beginning_equity, beginning_equity);
+
+    let days = match (period.first(), period.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp) as f64 / NANOS_PER_DAY,
+        _ => 0.0,
+    };
+    let annualized_return = if days >= 1.0 && total_return > -1.0 {
+        (1.0 + total_return).powf(365.0 / days) - 1.0
+    } else {
+        total_return
+    };
+
+    let returns = pct_changes(&daily_equity);
+    let daily_risk_free = config.risk_free_rate / config.periods_per_year;
+    let excess = mean(returns.iter().map(|r| r - daily_risk_free));
+    let daily_std = std_dev(&returns);
+    let downside_std = downside_dev(&returns, daily_risk_free);
+    let annualizer = config.periods_per_year.sqrt();
+
+    EquityStats {
+        net_profit: ending_equity - beginning_equity,
+        beginning_equity,
+        ending_equity,
+        total_return,
+        annualized_return,
+        daily_standard_deviation_percentage: daily_std,
+        annual_standard_deviation_percentage: daily_std * annualizer,
+        max_drawdown_percentage_period: max_drawdown(&period_equity),
+        max_drawdown_percentage_daily: max_drawdown(&daily_equity),
+        sharpe_ratio: ratio(excess, daily_std) * annualizer,
+        sortino_ratio: ratio(excess, downside_std) * annualizer,
+    }
+}
+
+pub fn backtest_stats(backtest: &BacktestData, config: &StatsConfig) -> ComputedStats {
+    ComputedStats {
+        trades: trade_stats(&backtest.trades),
+        equity: equity_stats(
+            &backtest.period_timeseries_stats,
+            &backtest.daily_timeseries_stats,
+            config,
+        ),
+    }
+}
+
+/// Live sessions don't carry timeseries, so only the trade stats apply.
+pub fn live_stats(live: &LiveData) -> TradeStats {
+    trade_stats(&live.trades)
+}
+
+/// Compares the reported `static_stats` with the stats recomputed from the trades and
+/// timeseries, returning the ones outside of `config.tolerance`.
+pub fn verify_backtest(backtest: &BacktestData, config: &StatsConfig) -> Result<Vec<Discrepancy>> {
+    let reported = serde_json::to_value(&backtest.metadata.static_stats)?;
+    let computed = serde_json::to_value(backtest_stats(backtest, config))?;
+
+    let (reported, computed) = match (reported, computed) {
+        (Value::Object(reported), Value::Object(computed)) => (reported, computed),
+        _ => return Ok(vec![]),
+    };
+
+    let mut discrepancies = Vec::new();
+
+    for (name, value) in computed {
+        let computed = value.as_f64().unwrap_or(0.0);
+        let reported = match reported.get(&name).and_then(Value::as_f64) {
+            Some(reported) => reported / scale_of(&name, config),
+            None => continue,
+        };
+
+        let allowed = config.tolerance * reported.abs().max(computed.abs()).max(1.0);
+        if (reported - computed).abs() > allowed {
+            discrepancies.push(Discrepancy {
+                name,
+                reported,
+                computed,
+            });
+        }
+    }
+
+    Ok(discrepancies)
+}
+
+/// Recomputed stats in the fixed-point scales of `static_stats`, i.e. what `verify_backtest`
+/// accepts for `backtest`.
+pub fn static_stats(backtest: &BacktestData, config: &StatsConfig) -> Result<StaticStats> {
+    let mut computed = serde_json::to_value(backtest_stats(backtest, config))?;
+
+    if let Value::Object(map) = &mut computed {
+        for (name, value) in map.iter_mut() {
+            let fixed = (value.as_f64().unwrap_or(0.0) * scale_of(name, config)).round();
+            *value = Value::from(fixed as i64);
+        }
+    }
+
+    Ok(serde_json::from_value(computed)?)
+}
+
+/// Divisor turning the fixed-point static stat `name` into its plain value, money stats are in
+/// the same `price_scale` units as the trades.
+pub fn display_scale(name: &str, config: &StatsConfig, price_scale: f64) -> f64 {
+    if MONEY_STATS.contains(&name) {
+        config.money_scale * price_scale
+    } else {
+        scale_of(name, config)
+    }
+}
+
+fn scale_of(name: &str, config: &StatsConfig) -> f64 {
+    if COUNT_STATS.contains(&name) {
+        1.0
+    } else if MONEY_STATS.contains(&name) {
+        config.money_scale
+    } else {
+        config.ratio_scale
+    }
+}
+
+fn close_trades(trades: &[Trades]) -> Vec<ClosedTrade> {
+    let mut positions: HashMap<&str, Position> = HashMap::new();
+    let mut closed = Vec::new();
+
+    for trade in trades {
+        let quantity = (trade.quantity as f64).abs();
+        if quantity == 0.0 {
+            continue;
+        }
+
+        // Trade value already includes any contract multiplier
+        let unit_value = (trade.trade_value as f64).abs() / quantity;
+        let side = if trade.action.eq_ignore_ascii_case("BUY") {
+            1.0
+        } else {
+            -1.0
+        };
+
+        let position = positions.entry(trade.ticker.as_str()).or_default();
+
+        if position.quantity == 0.0 || position.quantity.signum() == side {
+            let held = position.quantity.abs();
+            position.avg_cost =
+                (held * position.avg_cost + quantity * unit_value) / (held + quantity);
+            position.quantity += side * quantity;
+            continue;
+        }
+
+        let closing = quantity.min(position.quantity.abs());
+        let pnl = closing * (unit_value - position.avg_cost) * position.quantity.signum();
+        closed.push(ClosedTrade {
+            pnl,
+            return_pct: ratio(pnl, closing * position.avg_cost),
+        });
+
+        position.quantity += side * quantity;
+        if quantity > closing {
+            position.avg_cost = unit_value;
+        } else if position.quantity == 0.0 {
+            position.avg_cost = 0.0;
+        }
+    }
+
+    closed
+}
+
+fn mean<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| {
+        (sum + value, count + 1)
+    });
+    ratio(sum, count as f64)
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+fn pct_changes(equity: &[f64]) -> Vec<f64> {
+    equity
+        .windows(2)
+        .map(|pair| ratio(pair[1] - pair[0], pair[0]))
+        .collect()
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let avg = mean(values.iter().copied());
+    let variance = values
+        .iter()
+        .map(|value| (value - avg).powi(2))
+        .sum::<f64>()
+        / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+fn downside_dev(values: &[f64], target: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let squares: f64 = values
+        .iter()
+        .map(|value| (value - target).min(0.0).powi(2))
+        .sum();
+    (squares / values.len() as f64).sqrt()
+}
+
+fn max_drawdown(equity: &[f64]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut drawdown: f64 = 0.0;
+
+    for value in equity {
+        peak = peak.max(*value);
+        drawdown = drawdown.min(ratio(value - peak, peak));
+    }
+
+    drawdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    fn trade(ticker: &str, action: &str, quantity: i64, price: i64) -> Trades {
+        let value = if action == "BUY" {
+            -quantity * price
+        } else {
+            quantity * price
+        };
+
+        serde_json::from_value(json!({
+            "trade_id": 1,
+            "signal_id": 1,
+            "timestamp": 1704903000,
+            "ticker": ticker,
+            "quantity": quantity,
+            "avg_price": price,
+            "trade_value": value,
+            "trade_cost": value,
+            "action": action,
+            "fees": 1
+        }))
+        .expect("Invalid trade")
+    }
+
+    fn equity_curve(values: &[i64]) -> Vec<TimeseriesStats> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                serde_json::from_value(json!({
+                    "timestamp": 1_704_067_200_000_000_000i64 + i as i64 * 86_400_000_000_000i64,
+                    "equity_value": value,
+                    "percent_drawdown": 0,
+                    "cumulative_return": 0,
+                    "period_return": 0,
+                    "daily_strategy_return": "0",
+                    "daily_benchmark_return": "0"
+                }))
+                .expect("Invalid timeseries stat")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trade_stats() {
+        let trades = vec![
+            trade("AAPL", "BUY", 10, 100),
+            trade("AAPL", "SELL", 10, 110),
+            trade("MSFT", "SELL", 5, 200),
+            trade("MSFT", "BUY", 5, 220),
+        ];
+
+        // Test
+        let stats = trade_stats(&trades);
+
+        // Validate
+        assert_eq!(stats.total_trades, 2.0);
+        assert_eq!(stats.total_winning_trades, 1.0);
+        assert_eq!(stats.total_losing_trades, 1.0);
+        assert_eq!(stats.avg_gain, 100.0);
+        assert_eq!(stats.avg_loss, -100.0);
+        assert_eq!(stats.avg_gain_percent, 0.1);
+        assert_eq!(stats.profitability_ratio, 0.5);
+        assert_eq!(stats.profit_factor, 1.0);
+        assert_eq!(stats.total_fees, -4.0);
+    }
+
+    #[test]
+    fn test_equity_stats() {
+        let daily = equity_curve(&[100, 110, 99, 120]);
+
+        // Test
+        let stats = equity_stats(&daily, &daily, &StatsConfig::default());
+
+        // Validate
+        assert_eq!(stats.net_profit, 20.0);
+        assert_eq!(stats.total_return, 0.2);
+        assert!((stats.max_drawdown_percentage_daily + 0.1).abs() < 1e-9);
+        assert!(stats.sharpe_ratio > 0.0);
+        assert!(stats.sortino_ratio > stats.sharpe_ratio);
+    }
+
+    #[test]
+    fn test_verify_backtest() -> Result<()> {
+        let mut backtest = load_backtest();
+        backtest.trades = vec![
+            trade("AAPL", "BUY", 10, 100),
+            trade("AAPL", "SELL", 10, 110),
+        ];
+        backtest.period_timeseries_stats = equity_curve(&[1000, 1001]);
+        backtest.daily_timeseries_stats = equity_curve(&[1000, 1001]);
+
+        // Worked by hand: one 10 share round trip for +100 (+10%) with 1 in fees per fill, and
+        // equity up 1 over a single day. Ratios at 1e6, money and counts unscaled.
+        let config = StatsConfig::default();
+        let mut stats = json!({
+            "total_trades": 1,
+            "total_winning_trades": 1,
+            "total_losing_trades": 0,
+            "avg_profit": 100,
+            "avg_profit_percent": 100000,
+            "avg_gain": 100,
+            "avg_gain_percent": 100000,
+            "avg_loss": 0,
+            "avg_loss_percent": 0,
+            "profitability_ratio": 1000000,
+            "profit_factor": 0,
+            "profit_and_loss_ratio": 0,
+            "total_fees": -2,
+            "net_profit": 1,
+            "beginning_equity": 1000,
+            "ending_equity": 1001,
+            "total_return": 1000,
+            "annualized_return": 440251,
+            "daily_standard_deviation_percentage": 0,
+            "annual_standard_deviation_percentage": 0,
+            "max_drawdown_percentage_period": 0,
+            "max_drawdown_percentage_daily": 0,
+            "sharpe_ratio": 0,
+            "sortino_ratio": 0
+        });
+        backtest.metadata.static_stats = serde_json::from_value(stats.clone())?;
+
+        // Test
+        let consistent = verify_backtest(&backtest, &config)?;
+        stats["total_trades"] = Value::from(153);
+        backtest.metadata.static_stats = serde_json::from_value(stats)?;
+        let inconsistent = verify_backtest(&backtest, &config)?;
+
+        // Validate
+        assert!(consistent.is_empty());
+        assert_eq!(inconsistent.len(), 1);
+        assert_eq!(inconsistent[0].name, "total_trades");
+        assert_eq!(inconsistent[0].reported, 153.0);
+        assert_eq!(inconsistent[0].computed, 1.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_stats() -> Result<()> {
+        let mut backtest = load_backtest();
+        let config = StatsConfig::default();
+
+        // Test
+        let stats = static_stats(&backtest, &config)?;
+        let reported = verify_backtest(&backtest, &config)?;
+        backtest.metadata.static_stats = stats.clone();
+        let recomputed = verify_backtest(&backtest, &config)?;
+
+        // Validate
+        assert!(!reported.is_empty());
+        assert!(recomputed.is_empty());
+        assert_eq!(
+            stats.total_trades,
+            stats.total_winning_trades + stats.total_losing_trades
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Backtest as returned by `Trading::list_backtest`, without trades, signals or timeseries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub id: i32,
+    pub backtest_name: String,
+    pub strategy_name: String,
+    /// Unix nanoseconds.
+    pub created_at: i64,
+    pub tickers: Vec<String>,
+    pub start: i64,
+    pub end: i64,
+    pub total_trades: i64,
+    pub net_profit: i64,
+    pub total_return: i64,
+    pub sharpe_ratio: i64,
+    pub sortino_ratio: i64,
+    pub max_drawdown_percentage_period: i64,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Live session as returned by `Trading::list_live`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveSummary {
+    pub id: i32,
+    pub strategy_name: String,
+    /// Unix nanoseconds.
+    pub created_at: i64,
+    pub tickers: Vec<String>,
+    pub start: i64,
+    pub end: i64,
+    pub total_trades: i64,
+    pub start_net_liquidation: i64,
+    pub end_net_liquidation: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    CreatedAt,
+    Name,
+    NetProfit,
+    TotalReturn,
+    SharpeRatio,
+}
+
+/// Filters, ordering and pagination for the list endpoints, encoded as query parameters.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strategy_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort_by: Option<SortBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    descending: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+}
+
+impl ListQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn strategy_name(mut self, strategy_name: &str) -> Self {
+        self.strategy_name = Some(strategy_name.to_string());
+        self
+    }
+
+    pub fn ticker(mut self, ticker: &str) -> Self {
+        self.ticker = Some(ticker.to_string());
+        self
+    }
+
+    pub fn sort_by(mut self, sort_by: SortBy, descending: bool) -> Self {
+        self.sort_by = Some(sort_by);
+        self.descending = Some(descending);
+        self
+    }
+
+    pub fn page(mut self, offset: u32, limit: u32) -> Self {
+        self.offset = Some(offset);
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Tags and free-text notes attached to a backtest, e.g. `strategy=cointegrationzscore`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacktestAnnotations {
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl BacktestAnnotations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the tag or replaces its value.
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn untag(mut self, key: &str) -> Self {
+        self.tags.remove(key);
+        self
+    }
+
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.notes = notes.to_string();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Above,
+    Below,
+}
+
+/// Filters for `Trading::search_backtests`, all set filters have to match.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchQuery {
+    #[serde(
+        skip_serializing_if = "BTreeMap::is_empty",
+        serialize_with = "serialize_tags"
+    )]
+    tags: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strategy_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<i64>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_thresholds"
+    )]
+    stats: Vec<(String, Bound, i64)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn strategy_name(mut self, strategy_name: &str) -> Self {
+        self.strategy_name = Some(strategy_name.to_string());
+        self
+    }
+
+    pub fn ticker(mut self, ticker: &str) -> Self {
+        self.ticker = Some(ticker.to_string());
+        self
+    }
+
+    /// Backtests overlapping `[start, end]`, unix nanoseconds.
+    pub fn between(mut self, start: i64, end: i64) -> Self {
+        self.start = Some(start);
+        self.end = Some(end);
+        self
+    }
+
+    /// Static stat strictly above the value, in the stored fixed-point units.
+    pub fn stat_above(mut self, stat: &str, value: i64) -> Self {
+        self.stats.push((stat.to_string(), Bound::Above, value));
+        self
+    }
+
+    /// Static stat strictly below the value, in the stored fixed-point units.
+    pub fn stat_below(mut self, stat: &str, value: i64) -> Self {
+        self.stats.push((stat.to_string(), Bound::Below, value));
+        self
+    }
+
+    pub fn page(mut self, offset: u32, limit: u32) -> Self {
+        self.offset = Some(offset);
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, so keys and values can contain the
+/// `,`, `=`, `<` and `>` separators. The server splits the list first, then decodes each part.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                escaped.push(byte as char)
+            }
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    escaped
+}
+
+fn serialize_tags<S>(
+    tags: &BTreeMap<String, String>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let joined = tags
+        .iter()
+        .map(|(key, value)| format!("{}={}", escape(key), escape(value)))
+        .collect::<Vec<String>>()
+        .join(",");
+    serializer.serialize_str(&joined)
+}
+
+fn serialize_thresholds<S>(
+    thresholds: &[(String, Bound, i64)],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let joined = thresholds
+        .iter()
+        .map(|(stat, bound, value)| {
+            let op = match bound {
+                Bound::Above => ">",
+                Bound::Below => "<",
+            };
+            format!("{}{}{}", escape(stat), op, value)
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    serializer.serialize_str(&joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_query_params() -> anyhow::Result<()> {
+        let query = ListQuery::new()
+            .strategy_name("cointegrationzscore")
+            .sort_by(SortBy::SharpeRatio, true)
+            .page(20, 10);
+
+        // Test
+        let encoded = serde_urlencoded::to_string(&query)?;
+
+        // Validate
+        assert_eq!(
+            encoded,
+            "strategy_name=cointegrationzscore&sort_by=sharpe_ratio&descending=true&limit=10&offset=20"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_query_params() -> anyhow::Result<()> {
+        let query = SearchQuery::new()
+            .tag("universe", "grains")
+            .tag("strategy", "cointegrationzscore")
+            .ticker("ZC.n.0")
+            .stat_above("sharpe_ratio", 1_000_000)
+            .stat_below("max_drawdown_percentage_period", 0);
+
+        // Test
+        let encoded = serde_urlencoded::to_string(&query)?;
+
+        // Validate
+        assert_eq!(
+            encoded,
+            "tags=strategy%3Dcointegrationzscore%2Cuniverse%3Dgrains&ticker=ZC.n.0\
+             &stats=sharpe_ratio%3E1000000%2Cmax_drawdown_percentage_period%3C0"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_query_escapes_values() -> anyhow::Result<()> {
+        let query = SearchQuery::new()
+            .tag("universe", "grains,softs")
+            .tag("note", "a=b c%");
+
+        // Test
+        let encoded = serde_urlencoded::to_string(&query)?;
+
+        // Validate
+        let tags = &serde_urlencoded::from_str::<Vec<(String, String)>>(&encoded)?[0].1;
+        assert_eq!(tags, "note=a%3Db%20c%25,universe=grains%2Csofts");
+        assert_eq!(tags.split(',').count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotations() {
+        let annotations = BacktestAnnotations::new()
+            .tag("strategy", "cointegrationzscore")
+            .tag("universe", "grains")
+            .untag("strategy")
+            .notes("Rerun with fees");
+
+        // Validate
+        assert_eq!(annotations.tags.len(), 1);
+        assert_eq!(annotations.tags["universe"], "grains");
+        assert_eq!(annotations.notes, "Rerun with fees");
+    }
+
+    #[test]
+    fn test_summary_from_json() -> anyhow::Result<()> {
+        let json = serde_json::json!({
+            "id": 1,
+            "backtest_name": "testing76543",
+            "strategy_name": "cointegrationzscore",
+            "created_at": 1704903000000000000_i64,
+            "tickers": ["AAPL"],
+            "start": 1704862800000000000_i64,
+            "end": 1704893000000000000_i64,
+            "total_trades": 153,
+            "net_profit": 42774,
+            "total_return": 4277,
+            "sharpe_ratio": 6771709,
+            "sortino_ratio": 13604839,
+            "max_drawdown_percentage_period": -13609
+        });
+
+        // Test
+        let summary: BacktestSummary = serde_json::from_value(json)?;
+
+        // Validate
+        assert_eq!(summary.created_at, 1704903000000000000);
+        assert_eq!(summary.tickers, vec!["AAPL".to_string()]);
+        assert_eq!(summary.sharpe_ratio, 6771709);
+        assert!(summary.tags.is_empty());
+
+        Ok(())
+    }
+}
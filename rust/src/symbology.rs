@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+/// Period during which a symbol mapped to a single concrete instrument.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingInterval {
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub instrument_id: u32,
+    pub ticker: String,
+}
+
+/// Mapping of a requested symbol, e.g. a continuous `HE.n.0`, to concrete instruments over time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolResolution {
+    pub symbol: String,
+    pub intervals: Vec<MappingInterval>,
+}
+
+impl SymbolResolution {
+    /// Returns the interval active at `ts`.
+    pub fn instrument_at(&self, ts: i64) -> Option<&MappingInterval> {
+        self.intervals
+            .iter()
+            .find(|interval| interval.start_ts <= ts && ts <= interval.end_ts)
+    }
+
+    /// Timestamps at which the symbol rolled from one instrument to another.
+    pub fn roll_dates(&self) -> Vec<i64> {
+        self.intervals
+            .windows(2)
+            .filter(|pair| pair[0].instrument_id != pair[1].instrument_id)
+            .map(|pair| pair[1].start_ts)
+            .collect()
+    }
+}
+
+/// Resolves `(instrument_id, ts)` from a record header back to the requested symbol and the
+/// raw ticker, e.g. to annotate records returned by `get_records`.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolResolver {
+    resolutions: Vec<SymbolResolution>,
+}
+
+impl SymbolResolver {
+    pub fn new(resolutions: Vec<SymbolResolution>) -> Self {
+        SymbolResolver { resolutions }
+    }
+
+    /// Adds resolutions, e.g. from another dataset or stype.
+    pub fn extend(&mut self, resolutions: Vec<SymbolResolution>) {
+        self.resolutions.extend(resolutions);
+    }
+
+    pub fn resolutions(&self) -> &[SymbolResolution] {
+        &self.resolutions
+    }
+
+    pub fn lookup(&self, instrument_id: u32, ts: i64) -> Option<(&str, &MappingInterval)> {
+        self.resolutions.iter().find_map(|resolution| {
+            resolution
+                .instrument_at(ts)
+                .filter(|interval| interval.instrument_id == instrument_id)
+                .map(|interval| (resolution.symbol.as_str(), interval))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy_resolution() -> SymbolResolution {
+        SymbolResolution {
+            symbol: "HE.n.0".to_string(),
+            intervals: vec![
+                MappingInterval {
+                    start_ts: 0,
+                    end_ts: 99,
+                    instrument_id: 1,
+                    ticker: "HEG4".to_string(),
+                },
+                MappingInterval {
+                    start_ts: 100,
+                    end_ts: 199,
+                    instrument_id: 2,
+                    ticker: "HEJ4".to_string(),
+                },
+                MappingInterval {
+                    start_ts: 200,
+                    end_ts: 299,
+                    instrument_id: 3,
+                    ticker: "HEK4".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_instrument_at() {
+        let resolution = dummy_resolution();
+
+        // Test
+        let interval = resolution.instrument_at(150).unwrap();
+
+        // Validate
+        assert_eq!(interval.ticker, "HEJ4");
+        assert!(resolution.instrument_at(300).is_none());
+    }
+
+    #[test]
+    fn test_roll_dates() {
+        let resolution = dummy_resolution();
+
+        // Test
+        let rolls = resolution.roll_dates();
+
+        // Validate
+        assert_eq!(rolls, vec![100, 200]);
+    }
+
+    #[test]
+    fn test_resolver_lookup() {
+        let resolver = SymbolResolver::new(vec![dummy_resolution()]);
+
+        // Test
+        let (symbol, interval) = resolver.lookup(3, 250).unwrap();
+
+        // Validate
+        assert_eq!(symbol, "HE.n.0");
+        assert_eq!(interval.ticker, "HEK4");
+        assert!(resolver.lookup(1, 250).is_none());
+    }
+}
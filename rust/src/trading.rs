@@ -1,11 +1,53 @@
+use crate::bundle::{market_data_metadata, remap_market_data, Bundle, BundleData};
+use crate::compare::{compare, Comparison};
+use crate::drift::{analyze, DriftConfig, DriftReport};
+use crate::historical::{Historical, DEFAULT_BATCH_SIZE};
+use crate::instrument::Instruments;
 use crate::response::ApiResponse;
+use crate::retention::RetentionPolicy;
+use crate::session::LiveUpdate;
+use crate::stats::{verify_backtest, StatsConfig};
+use crate::summary::{BacktestAnnotations, BacktestSummary, ListQuery, LiveSummary, SearchQuery};
+use crate::upload::{encode_backtest, encode_json_file, BacktestChunks, DEFAULT_CHUNK_ITEMS};
+use crate::watch::{LiveDiff, LiveEvent};
 use crate::{error::Error, error::Result};
+use chrono::Utc;
+use futures_util::stream::{self, BoxStream};
 use futures_util::StreamExt;
-use mbinary::backtest_encode::BacktestEncoder;
+use mbinary::backtest::{BacktestMetaData, Parameters, Signals, TimeseriesStats, Trades};
+use mbinary::params::RetrieveParams;
 use mbinary::{backtest::BacktestData, live::LiveData};
-use reqwest::StatusCode;
 use reqwest::{self, Client, ClientBuilder};
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+const RETENTION_PAGE_SIZE: u32 = 500;
+
+/// Consecutive failed polls after which `Trading::watch_live` ends its stream.
+pub const WATCH_MAX_ERRORS: usize = 5;
+
+/// Timeseries stored with a backtest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeseriesPeriod {
+    Period,
+    Daily,
+}
+
+impl TimeseriesPeriod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TimeseriesPeriod::Period => "period",
+            TimeseriesPeriod::Daily => "daily",
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Trading {
@@ -27,12 +69,7 @@ impl Trading {
     }
 
     fn url(&self, endpoint: &str) -> String {
-        format!(
-            "{}{}{}",
-            self.base_url,
-            "/trading/".to_string(),
-            endpoint.to_string()
-        )
+        format!("{}{}{}", self.base_url, "/trading/", endpoint)
     }
 
     // Live
@@ -50,17 +87,62 @@ impl Trading {
         Ok(api_response)
     }
 
-    pub async fn list_live(&self) -> Result<ApiResponse<Vec<(i32, String)>>> {
+    /// Opens an empty live session for incremental updates, returns data = session id.
+    pub async fn start_live(&self, parameters: &Parameters) -> Result<ApiResponse<i32>> {
+        let url = self.url("live/session/start");
+        let response = self.client.post(&url).json(parameters).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<i32>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<i32>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Appends trades, signals and the latest account snapshot to an open live session.
+    pub async fn append_live(&self, id: &i32, update: &LiveUpdate) -> Result<ApiResponse<String>> {
+        let url = self.url(&format!("live/session/append?id={}", id));
+        let response = self.client.post(&url).json(update).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<String>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Closes a live session, no updates are accepted afterwards.
+    pub async fn finalize_live(&self, id: &i32) -> Result<ApiResponse<String>> {
+        let url = self.url(&format!("live/session/finalize?id={}", id));
+        let response = self.client.post(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<String>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    pub async fn list_live(&self, query: &ListQuery) -> Result<ApiResponse<Vec<LiveSummary>>> {
         let url = self.url("live/list");
-        let response = self.client.get(&url).send().await?;
+        let response = self.client.get(&url).query(query).send().await?;
 
         // Check for HTTP status
         if response.status() != StatusCode::OK {
             // Deserialize the API response and return it, even if it indicates failure
-            return ApiResponse::<Vec<(i32, String)>>::from_response(response).await;
+            return ApiResponse::<Vec<LiveSummary>>::from_response(response).await;
         }
 
-        let api_response = ApiResponse::<Vec<(i32, String)>>::from_response(response).await?;
+        let api_response = ApiResponse::<Vec<LiveSummary>>::from_response(response).await?;
         Ok(api_response)
     }
 
@@ -91,19 +173,158 @@ impl Trading {
         Ok(api_response)
     }
 
+    /// Streams new trades, signals and account snapshots of a live session. The server has no
+    /// push endpoint, so the session is polled every `interval` and diffed client side. The
+    /// session is read once before returning and items already present are not emitted.
+    ///
+    /// Failed polls are yielded as errors and polling continues, after `WATCH_MAX_ERRORS`
+    /// consecutive failures the last error is yielded and the stream ends. The stream also
+    /// ends once the session is gone (404 or no data). Sessions carry no finalized flag, so a
+    /// finalized session is watched until it is deleted or the stream is dropped.
+    pub async fn watch_live(
+        &self,
+        id: i32,
+        interval: Duration,
+    ) -> Result<BoxStream<'static, Result<LiveEvent>>> {
+        let response = self.get_live(&id).await?;
+        if response.status != "success" {
+            return Err(Error::CustomError(response.message));
+        }
+
+        let mut diff = LiveDiff::new();
+        if let Some(data) = response.data.first() {
+            diff.update(data)?;
+        }
+
+        Ok(self.poll_live(id, interval, diff))
+    }
+
+    fn poll_live(
+        &self,
+        id: i32,
+        interval: Duration,
+        diff: LiveDiff,
+    ) -> BoxStream<'static, Result<LiveEvent>> {
+        // (client, diff, queued events, consecutive errors)
+        let state = (self.clone(), diff, VecDeque::new(), 0);
+
+        stream::unfold(
+            state,
+            move |(client, mut diff, mut queue, mut errors)| async move {
+                if let Some(event) = queue.pop_front() {
+                    return Some((Ok(event), (client, diff, queue, errors)));
+                }
+                if errors >= WATCH_MAX_ERRORS {
+                    return None;
+                }
+
+                loop {
+                    tokio::time::sleep(interval).await;
+
+                    let polled = match client.get_live(&id).await {
+                        Ok(response) if response.code == StatusCode::NOT_FOUND.as_u16() => {
+                            return None
+                        }
+                        Ok(response) if response.status != "success" => {
+                            Err(Error::CustomError(response.message))
+                        }
+                        Ok(response) => match response.data.first() {
+                            Some(data) => diff.update(data),
+                            None => return None,
+                        },
+                        Err(e) => Err(e),
+                    };
+
+                    match polled {
+                        Ok(events) => {
+                            errors = 0;
+                            queue.extend(events);
+                        }
+                        Err(e) => {
+                            errors += 1;
+                            return Some((Err(e), (client, diff, queue, errors)));
+                        }
+                    }
+
+                    if let Some(event) = queue.pop_front() {
+                        return Some((Ok(event), (client, diff, queue, errors)));
+                    }
+                }
+            },
+        )
+        .boxed()
+    }
+
     // Backtest
     pub async fn create_backtest(&self, backtest: &BacktestData) -> Result<ApiResponse<String>> {
-        let mut bytes = Vec::new();
-        let mut encoder = BacktestEncoder::new(&mut bytes);
-        encoder.encode_metadata(&backtest.metadata);
-        encoder.encode_timeseries(&backtest.period_timeseries_stats);
-        encoder.encode_timeseries(&backtest.daily_timeseries_stats);
-        encoder.encode_trades(&backtest.trades);
-        encoder.encode_signals(&backtest.signals);
-
-        // Convert bytes into a stream for sending
+        let bytes = encode_backtest(backtest);
+
+        self.send_backtest(reqwest::Body::from(bytes)).await
+    }
+
+    /// Encodes the backtest while the request body is sent, at most `DEFAULT_CHUNK_ITEMS`
+    /// trades or signals at a time, so the encoded backtest is never held in memory as a whole.
+    /// The decoded `backtest` still is, use `create_backtest_from_file` on a `.json` file to
+    /// avoid loading it.
+    pub async fn create_backtest_streamed(
+        &self,
+        backtest: BacktestData,
+    ) -> Result<ApiResponse<String>> {
+        let chunks = BacktestChunks::new(backtest, DEFAULT_CHUNK_ITEMS);
+        let body = stream::iter(chunks.map(Ok::<_, std::io::Error>));
+
+        self.send_backtest(reqwest::Body::wrap_stream(body)).await
+    }
+
+    /// Uploads a backtest file, `.json` files are read incrementally and stream-encoded, any
+    /// other file is expected to be `BacktestEncoder` output and is streamed from disk as is.
+    pub async fn create_backtest_from_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<ApiResponse<String>> {
+        let path = path.as_ref();
+        let is_json = path
+            .extension()
+            .map(|extension| extension.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        if is_json {
+            let (tx, rx) = mpsc::channel::<std::io::Result<Vec<u8>>>(4);
+            let path = path.to_path_buf();
+
+            tokio::task::spawn_blocking(move || {
+                let encoded = encode_json_file(&path, DEFAULT_CHUNK_ITEMS, |bytes| {
+                    tx.blocking_send(Ok(bytes))
+                        .map_err(|_| Error::CustomError("Backtest upload was aborted.".into()))
+                });
+                if let Err(e) = encoded {
+                    let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+                }
+            });
+
+            let body = ReceiverStream::new(rx);
+            return self.send_backtest(reqwest::Body::wrap_stream(body)).await;
+        }
+
+        let file = tokio::fs::File::open(path).await?;
+        let chunks = stream::unfold(Some(file), |file| async move {
+            let mut file = file?;
+            let mut buffer = vec![0u8; FILE_CHUNK_SIZE];
+            match file.read(&mut buffer).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buffer.truncate(n);
+                    Some((Ok(buffer), Some(file)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+
+        self.send_backtest(reqwest::Body::wrap_stream(chunks)).await
+    }
+
+    async fn send_backtest(&self, body: reqwest::Body) -> Result<ApiResponse<String>> {
         let url = self.url("backtest/create");
-        let body = reqwest::Body::from(bytes);
         let response = self.client.post(&url).body(body).send().await?;
 
         // Check for HTTP status
@@ -145,7 +366,7 @@ impl Trading {
             }
         }
 
-        if last_response.len() > 0 {
+        if !last_response.is_empty() {
             Ok(last_response[0].clone())
         } else {
             Ok(ApiResponse::new(
@@ -157,17 +378,106 @@ impl Trading {
         }
     }
 
-    pub async fn list_backtest(&self) -> Result<ApiResponse<Vec<(i32, String)>>> {
+    /// Recomputes the static stats and only uploads the backtest when they match, returns
+    /// status "failed" listing the mismatching stats otherwise.
+    pub async fn create_backtest_verified(
+        &self,
+        backtest: &BacktestData,
+        config: &StatsConfig,
+    ) -> Result<ApiResponse<String>> {
+        let discrepancies = verify_backtest(backtest, config)?;
+
+        if !discrepancies.is_empty() {
+            let stats: Vec<String> = discrepancies
+                .iter()
+                .map(|d| {
+                    format!(
+                        "{} (reported {}, computed {})",
+                        d.name, d.reported, d.computed
+                    )
+                })
+                .collect();
+
+            return Ok(ApiResponse::new(
+                "failed",
+                &format!("Static stats don't match the data: {}", stats.join(", ")),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "".to_string(),
+            ));
+        }
+
+        self.create_backtest(backtest).await
+    }
+
+    pub async fn list_backtest(
+        &self,
+        query: &ListQuery,
+    ) -> Result<ApiResponse<Vec<BacktestSummary>>> {
         let url = self.url("backtest/list");
+        let response = self.client.get(&url).query(query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<BacktestSummary>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<BacktestSummary>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Backtests matching all filters of the query, e.g. a tag and `sharpe_ratio` above a threshold.
+    pub async fn search_backtests(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<ApiResponse<Vec<BacktestSummary>>> {
+        let url = self.url("backtest/search");
+        let response = self.client.get(&url).query(query).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<BacktestSummary>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<BacktestSummary>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    pub async fn get_backtest_annotations(
+        &self,
+        id: &i32,
+    ) -> Result<ApiResponse<Option<BacktestAnnotations>>> {
+        let url = self.url(&format!("backtest/annotations?id={}", id));
         let response = self.client.get(&url).send().await?;
 
         // Check for HTTP status
         if response.status() != StatusCode::OK {
             // Deserialize the API response and return it, even if it indicates failure
-            return ApiResponse::<Vec<(i32, String)>>::from_response(response).await;
+            return ApiResponse::<Option<BacktestAnnotations>>::from_response(response).await;
+        }
+
+        let api_response =
+            ApiResponse::<Option<BacktestAnnotations>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Replaces the tags and notes of a backtest.
+    pub async fn update_backtest_annotations(
+        &self,
+        id: &i32,
+        annotations: &BacktestAnnotations,
+    ) -> Result<ApiResponse<String>> {
+        let url = self.url(&format!("backtest/annotations?id={}", id));
+        let response = self.client.put(&url).json(annotations).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<String>::from_response(response).await;
         }
 
-        let api_response = ApiResponse::<Vec<(i32, String)>>::from_response(response).await?;
+        let api_response = ApiResponse::<String>::from_response(response).await?;
         Ok(api_response)
     }
 
@@ -185,24 +495,499 @@ impl Trading {
         Ok(api_response)
     }
 
-    pub async fn get_backtest(&self, id: &i32) -> Result<ApiResponse<Vec<BacktestData>>> {
+    /// Deletes the backtests in a single request, returns data = deleted ids. A dry run only
+    /// returns the ids that would be deleted.
+    pub async fn delete_backtests(
+        &self,
+        ids: &[i32],
+        dry_run: bool,
+    ) -> Result<ApiResponse<Vec<i32>>> {
+        if dry_run || ids.is_empty() {
+            let message = if dry_run {
+                format!("Dry run, {} backtests would be deleted.", ids.len())
+            } else {
+                "No backtests to delete.".to_string()
+            };
+            return Ok(ApiResponse::new(
+                "success",
+                &message,
+                StatusCode::OK,
+                ids.to_vec(),
+            ));
+        }
+
+        let url = self.url("backtest/delete/batch");
+        let response = self.client.delete(&url).json(ids).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<i32>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<i32>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    /// Deletes every backtest matching the search, paging through all results. The paging set
+    /// on `query` is ignored.
+    pub async fn delete_backtests_matching(
+        &self,
+        query: &SearchQuery,
+        dry_run: bool,
+    ) -> Result<ApiResponse<Vec<i32>>> {
+        let mut ids = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let page = query.clone().page(offset, RETENTION_PAGE_SIZE);
+            let response = self.search_backtests(&page).await?;
+
+            if response.status != "success" {
+                return Ok(ApiResponse::with_default(
+                    &response.status,
+                    &response.message,
+                    response.code,
+                ));
+            }
+
+            let count = response.data.len() as u32;
+            ids.extend(response.data.iter().map(|summary| summary.id));
+            if count < RETENTION_PAGE_SIZE {
+                break;
+            }
+            offset += count;
+        }
+
+        self.delete_backtests(&ids, dry_run).await
+    }
+
+    /// Lists every backtest, applies the retention policy and deletes what it doesn't keep.
+    pub async fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+    ) -> Result<ApiResponse<Vec<i32>>> {
+        let mut backtests = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let query = ListQuery::new().page(offset, RETENTION_PAGE_SIZE);
+            let response = self.list_backtest(&query).await?;
+
+            if response.status != "success" {
+                return Ok(ApiResponse::with_default(
+                    &response.status,
+                    &response.message,
+                    response.code,
+                ));
+            }
+
+            let count = response.data.len() as u32;
+            backtests.extend(response.data);
+            if count < RETENTION_PAGE_SIZE {
+                break;
+            }
+            offset += count;
+        }
+
+        let now = Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX);
+        let plan = policy.plan(&backtests, now)?;
+        self.delete_backtests(&plan.delete, dry_run).await
+    }
+
+    pub async fn get_backtest(&self, id: &i32) -> Result<ApiResponse<Option<BacktestData>>> {
         let url = self.url(&format!("backtest/get?id={}", id));
         let response = self.client.get(&url).send().await?;
 
         // Check for HTTP status
         if response.status() != StatusCode::OK {
             // Deserialize the API response and return it, even if it indicates failure
-            return ApiResponse::<Vec<BacktestData>>::from_response(response).await;
+            return ApiResponse::<Option<BacktestData>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<BacktestData>>::from_response(response).await?;
+        Ok(first_item(api_response))
+    }
+
+    /// Fetches the backtests and compares them, the first one is the baseline.
+    pub async fn compare_backtests(&self, ids: &[i32]) -> Result<Comparison> {
+        let mut backtests = Vec::new();
+
+        for id in ids {
+            let response = self.get_backtest(id).await?;
+            match response.data {
+                Some(backtest) => backtests.push(backtest),
+                None => {
+                    return Err(Error::CustomError(format!(
+                        "Backtest {} not found: {}",
+                        id, response.message
+                    )))
+                }
+            }
+        }
+
+        compare(&backtests)
+    }
+
+    /// Fetches a live session and its backtest and analyzes how far the live trading drifted.
+    pub async fn live_drift(
+        &self,
+        live_id: &i32,
+        backtest_id: &i32,
+        config: &DriftConfig,
+    ) -> Result<DriftReport> {
+        let live_response = self.get_live(live_id).await?;
+        let live = match live_response.data.into_iter().next() {
+            Some(live) => live,
+            None => {
+                return Err(Error::CustomError(format!(
+                    "Live session {} not found: {}",
+                    live_id, live_response.message
+                )))
+            }
+        };
+
+        let backtest_response = self.get_backtest(backtest_id).await?;
+        let backtest = match backtest_response.data {
+            Some(backtest) => backtest,
+            None => {
+                return Err(Error::CustomError(format!(
+                    "Backtest {} not found: {}",
+                    backtest_id, backtest_response.message
+                )))
+            }
+        };
+
+        Ok(analyze(&live, &backtest, config))
+    }
+
+    pub async fn get_backtest_by_name(
+        &self,
+        name: &str,
+    ) -> Result<ApiResponse<Option<BacktestData>>> {
+        let url = self.url("backtest/get");
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("name", name)])
+            .send()
+            .await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Option<BacktestData>>::from_response(response).await;
         }
 
         let api_response = ApiResponse::<Vec<BacktestData>>::from_response(response).await?;
+        Ok(first_item(api_response))
+    }
+
+    /// Returns data = parameters and static stats only.
+    pub async fn get_backtest_metadata(
+        &self,
+        id: &i32,
+    ) -> Result<ApiResponse<Option<BacktestMetaData>>> {
+        let url = self.url(&format!("backtest/get/metadata?id={}", id));
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Option<BacktestMetaData>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Option<BacktestMetaData>>::from_response(response).await?;
+        Ok(api_response)
+    }
+
+    pub async fn get_backtest_timeseries(
+        &self,
+        id: &i32,
+        period: TimeseriesPeriod,
+    ) -> Result<ApiResponse<Vec<TimeseriesStats>>> {
+        let url = self.url(&format!(
+            "backtest/get/timeseries?id={}&period={}",
+            id,
+            period.as_str()
+        ));
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP status
+        if response.status() != StatusCode::OK {
+            // Deserialize the API response and return it, even if it indicates failure
+            return ApiResponse::<Vec<TimeseriesStats>>::from_response(response).await;
+        }
+
+        let api_response = ApiResponse::<Vec<TimeseriesStats>>::from_response(response).await?;
         Ok(api_response)
     }
+
+    /// Writes the backtest to a `.tar.gz` bundle, with the market data it ran on when
+    /// `market_data` is given.
+    pub async fn export_backtest_bundle<P: AsRef<Path>>(
+        &self,
+        id: &i32,
+        path: P,
+        market_data: Option<(&Historical, &RetrieveParams)>,
+    ) -> Result<()> {
+        let response = self.get_backtest(id).await?;
+        let backtest = match response.data {
+            Some(backtest) => backtest,
+            None => {
+                return Err(Error::CustomError(format!(
+                    "Backtest {} not found: {}",
+                    id, response.message
+                )))
+            }
+        };
+
+        let market_data = fetch_market_data(market_data).await?;
+        write_bundle(Bundle::backtest(backtest, market_data), path.as_ref()).await
+    }
+
+    /// Recreates a backtest from a bundle, returns data = new backtest id. When `market_data`
+    /// is given the bundled market data is uploaded first, re-keyed onto the instrument ids of
+    /// the same tickers on this server.
+    pub async fn import_backtest_bundle<P: AsRef<Path>>(
+        &self,
+        path: P,
+        market_data: Option<(&Historical, &Instruments)>,
+    ) -> Result<ApiResponse<String>> {
+        let bundle = read_bundle(path.as_ref()).await?;
+
+        if let Some(response) = upload_market_data(market_data, &bundle.market_data).await? {
+            return Ok(response);
+        }
+
+        match bundle.data {
+            BundleData::Backtest(backtest) => self.create_backtest_streamed(backtest).await,
+            BundleData::Live(_) => Err(Error::CustomError(
+                "Bundle holds a live session, use import_live_bundle.".to_string(),
+            )),
+        }
+    }
+
+    /// Writes the live session to a `.tar.gz` bundle, with the market data it ran on when
+    /// `market_data` is given.
+    pub async fn export_live_bundle<P: AsRef<Path>>(
+        &self,
+        id: &i32,
+        path: P,
+        market_data: Option<(&Historical, &RetrieveParams)>,
+    ) -> Result<()> {
+        let response = self.get_live(id).await?;
+        let live = match response.data.into_iter().next() {
+            Some(live) => live,
+            None => {
+                return Err(Error::CustomError(format!(
+                    "Live session {} not found: {}",
+                    id, response.message
+                )))
+            }
+        };
+
+        let market_data = fetch_market_data(market_data).await?;
+        write_bundle(Bundle::live(live, market_data), path.as_ref()).await
+    }
+
+    /// Recreates a live session from a bundle. When `market_data` is given the bundled market
+    /// data is uploaded first, re-keyed onto the instrument ids of the same tickers on this
+    /// server.
+    pub async fn import_live_bundle<P: AsRef<Path>>(
+        &self,
+        path: P,
+        market_data: Option<(&Historical, &Instruments)>,
+    ) -> Result<ApiResponse<i32>> {
+        let bundle = read_bundle(path.as_ref()).await?;
+
+        if let Some(response) = upload_market_data(market_data, &bundle.market_data).await? {
+            return Ok(ApiResponse::with_default(
+                &response.status,
+                &response.message,
+                response.code,
+            ));
+        }
+
+        match bundle.data {
+            BundleData::Live(live) => self.create_live(&live).await,
+            BundleData::Backtest(_) => Err(Error::CustomError(
+                "Bundle holds a backtest, use import_backtest_bundle.".to_string(),
+            )),
+        }
+    }
+
+    /// Streams the trades of a backtest one at a time instead of loading them all at once.
+    pub async fn stream_backtest_trades(
+        &self,
+        id: &i32,
+    ) -> Result<BoxStream<'static, Result<Trades>>> {
+        let url = self.url(&format!("backtest/get/trades?id={}", id));
+        let response = self.client.get(&url).send().await?;
+
+        if response.status() != StatusCode::OK {
+            let api_response = ApiResponse::<String>::from_response(response).await?;
+            return Err(Error::CustomError(api_response.message));
+        }
+
+        Ok(ndjson_stream(response))
+    }
+
+    /// Streams the signals of a backtest one at a time instead of loading them all at once.
+    pub async fn stream_backtest_signals(
+        &self,
+        id: &i32,
+    ) -> Result<BoxStream<'static, Result<Signals>>> {
+        let url = self.url(&format!("backtest/get/signals?id={}", id));
+        let response = self.client.get(&url).send().await?;
+
+        if response.status() != StatusCode::OK {
+            let api_response = ApiResponse::<String>::from_response(response).await?;
+            return Err(Error::CustomError(api_response.message));
+        }
+
+        Ok(ndjson_stream(response))
+    }
+}
+
+async fn fetch_market_data(
+    market_data: Option<(&Historical, &RetrieveParams)>,
+) -> Result<Option<Vec<u8>>> {
+    let (historical, params) = match market_data {
+        Some(market_data) => market_data,
+        None => return Ok(None),
+    };
+
+    let response = historical.get_records(params).await?;
+    if response.status != "success" {
+        return Err(Error::CustomError(format!(
+            "Failed to fetch market data: {}",
+            response.message
+        )));
+    }
+
+    Ok(Some(response.data))
+}
+
+/// Bundles are tar and gzip on std::fs, so they are written on the blocking pool.
+async fn write_bundle(bundle: Bundle, path: &Path) -> Result<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || bundle.write(path))
+        .await
+        .map_err(|e| Error::CustomError(format!("Bundle write failed: {}", e)))?
+}
+
+async fn read_bundle(path: &Path) -> Result<Bundle> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || Bundle::read(path))
+        .await
+        .map_err(|e| Error::CustomError(format!("Bundle read failed: {}", e)))?
+}
+
+/// Uploads the bundled market data, returns the response when the upload failed. Instrument
+/// ids differ between servers, so each bundled ticker is looked up on the target server and
+/// the records are re-keyed, the import is refused when a ticker is missing.
+async fn upload_market_data(
+    market_data: Option<(&Historical, &Instruments)>,
+    bytes: &Option<Vec<u8>>,
+) -> Result<Option<ApiResponse<String>>> {
+    let ((historical, instruments), bytes) = match (market_data, bytes) {
+        (Some(market_data), Some(bytes)) => (market_data, bytes),
+        _ => return Ok(None),
+    };
+
+    let metadata = market_data_metadata(bytes)?;
+    let mut ids: HashMap<String, u32> = HashMap::new();
+    for ticker in metadata.mappings.map.values() {
+        let response = instruments.get_symbol(ticker, &metadata.dataset).await?;
+        let id = response
+            .data
+            .iter()
+            .filter(|instrument| instrument.ticker == *ticker)
+            .find_map(|instrument| instrument.instrument_id);
+
+        match id {
+            Some(id) => {
+                ids.insert(ticker.clone(), id);
+            }
+            None => {
+                return Err(Error::CustomError(format!(
+                    "Instrument {} not found on the target server, create it before importing \
+                     the bundle.",
+                    ticker
+                )))
+            }
+        }
+    }
+
+    let remapped = remap_market_data(bytes, &ids)?;
+    let reader = std::io::Cursor::new(remapped.as_slice());
+    let response = historical
+        .create_mbp_batched(reader, DEFAULT_BATCH_SIZE)
+        .await?;
+
+    if response.status != "success" {
+        return Ok(Some(response));
+    }
+    Ok(None)
+}
+
+fn first_item<T>(response: ApiResponse<Vec<T>>) -> ApiResponse<Option<T>> {
+    ApiResponse {
+        status: response.status,
+        message: response.message,
+        code: response.code,
+        data: response.data.into_iter().next(),
+    }
+}
+
+/// Parses a newline delimited JSON body into a stream of items as the chunks arrive.
+fn ndjson_stream<T>(response: Response) -> BoxStream<'static, Result<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let state = (response.bytes_stream().boxed(), Vec::<u8>::new(), false);
+
+    stream::unfold(state, |(mut bytes, mut buffer, mut done)| async move {
+        loop {
+            if let Some(position) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=position).collect();
+                if line.iter().all(|byte| byte.is_ascii_whitespace()) {
+                    continue;
+                }
+                let item = serde_json::from_slice::<T>(&line).map_err(Error::from);
+                return Some((item, (bytes, buffer, done)));
+            }
+
+            if done {
+                if buffer.iter().all(|byte| byte.is_ascii_whitespace()) {
+                    return None;
+                }
+                let line = std::mem::take(&mut buffer);
+                let item = serde_json::from_slice::<T>(&line).map_err(Error::from);
+                return Some((item, (bytes, buffer, done)));
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    buffer.clear();
+                    return Some((Err(Error::from(e)), (bytes, buffer, true)));
+                }
+                None => done = true,
+            }
+        }
+    })
+    .boxed()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::LiveSession;
+    use crate::stats::static_stats;
+    use crate::summary::SortBy;
     use dotenv::dotenv;
     use regex::Regex;
     use serial_test::serial;
@@ -248,6 +1033,70 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_create_backtest_from_file() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Test
+        let response = client
+            .create_backtest_from_file("tests/data/test_data.backtest.json")
+            .await?;
+
+        // Validate
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        let id: i32 = response.data.parse().unwrap();
+        let _ = client.delete_backtest(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_create_backtest_verified() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest_data: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let config = StatsConfig::default();
+        let mut verified_data = backtest_data.clone();
+        verified_data.metadata.static_stats = static_stats(&verified_data, &config)?;
+
+        // Test
+        let rejected = client
+            .create_backtest_verified(&backtest_data, &config)
+            .await?;
+        let response = client
+            .create_backtest_verified(&verified_data, &config)
+            .await?;
+
+        // Validate
+        assert_eq!(rejected.code, 422);
+        assert_eq!(rejected.status, "failed");
+        assert!(rejected.message.contains("total_trades"));
+        assert_eq!(response.code, 200);
+        assert_eq!(response.status, "success");
+
+        // Cleanup
+        let id: i32 = response.data.parse().unwrap();
+        let _ = client.delete_backtest(&id).await?;
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     // #[ignore]
@@ -266,11 +1115,132 @@ mod tests {
         let id: i32 = response.data.parse().unwrap();
 
         // Test
-        let response = client.list_backtest().await?;
+        let query = ListQuery::new()
+            .strategy_name("cointegrationzscore")
+            .sort_by(SortBy::CreatedAt, true)
+            .page(0, 10);
+        let response = client.list_backtest(&query).await?;
 
         // Validate
         assert_eq!(response.code, 200);
         assert_eq!(response.status, "success");
+        assert_eq!(response.data[0].id, id);
+        assert_eq!(response.data[0].tickers, vec!["AAPL".to_string()]);
+
+        // Cleanup
+        let _ = client.delete_backtest(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_backtest_annotations() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest_data: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let response = client.create_backtest(&backtest_data).await?;
+        let id: i32 = response.data.parse().unwrap();
+
+        // Test
+        let annotations = BacktestAnnotations::new()
+            .tag("strategy", "cointegrationzscore")
+            .tag("universe", "tech")
+            .notes("Baseline run");
+        let updated = client
+            .update_backtest_annotations(&id, &annotations)
+            .await?;
+        let stored = client.get_backtest_annotations(&id).await?;
+        let query = SearchQuery::new()
+            .tag("universe", "tech")
+            .stat_above("sharpe_ratio", 0);
+        let found = client.search_backtests(&query).await?;
+
+        // Validate
+        assert_eq!(updated.status, "success");
+        assert_eq!(stored.data, Some(annotations));
+        assert!(found.data.iter().any(|summary| summary.id == id));
+
+        // Cleanup
+        let _ = client.delete_backtest(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_delete_backtests() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest_data: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let response = client.create_backtest(&backtest_data).await?;
+            ids.push(response.data.parse::<i32>().unwrap());
+        }
+
+        // Test
+        let dry_run = client.delete_backtests(&ids, true).await?;
+        let still_there = client.get_backtest(&ids[0]).await?;
+        let deleted = client.delete_backtests(&ids, false).await?;
+        let gone = client.get_backtest(&ids[0]).await?;
+
+        // Validate
+        assert_eq!(dry_run.data, ids);
+        assert!(still_there.data.is_some());
+        assert_eq!(deleted.status, "success");
+        assert_eq!(deleted.data.len(), ids.len());
+        assert!(gone.data.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_apply_retention_dry_run() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest_data: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let response = client.create_backtest(&backtest_data).await?;
+        let id: i32 = response.data.parse().unwrap();
+
+        // Test
+        let query = ListQuery::new().sort_by(SortBy::CreatedAt, true).page(0, 1);
+        let listed = client.list_backtest(&query).await?;
+        let policy = RetentionPolicy::new().max_age(Duration::from_secs(86_400));
+        let planned = client.apply_retention(&policy, true).await?;
+
+        // Validate
+        let now = Utc::now().timestamp_nanos_opt().unwrap();
+        let created_at = listed.data[0].created_at;
+        assert_eq!(listed.data[0].id, id);
+        assert!((now - created_at).abs() < 3_600_000_000_000);
+        assert_eq!(planned.status, "success");
+        assert!(!planned.data.contains(&id));
 
         // Cleanup
         let _ = client.delete_backtest(&id).await?;
@@ -301,6 +1271,55 @@ mod tests {
         // Validate
         assert_eq!(response.code, 200);
         assert_eq!(response.status, "success");
+        assert_eq!(
+            response.data.unwrap().metadata.backtest_name,
+            backtest_data.metadata.backtest_name
+        );
+
+        // Cleanup
+        let _ = client.delete_backtest(&id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_get_backtest_sections() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        let backtest_data: BacktestData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let response = client.create_backtest(&backtest_data).await?;
+        let id: i32 = response.data.parse().unwrap();
+
+        // Test
+        let by_name = client
+            .get_backtest_by_name(&backtest_data.metadata.backtest_name)
+            .await?;
+        let metadata = client.get_backtest_metadata(&id).await?;
+        let daily = client
+            .get_backtest_timeseries(&id, TimeseriesPeriod::Daily)
+            .await?;
+        let mut trades = client.stream_backtest_trades(&id).await?;
+        let mut trade_count = 0;
+        while let Some(trade) = trades.next().await {
+            let _ = trade?;
+            trade_count += 1;
+        }
+
+        // Validate
+        assert!(by_name.data.is_some());
+        assert_eq!(metadata.status, "success");
+        assert!(metadata.data.is_some());
+        assert_eq!(daily.data.len(), backtest_data.daily_timeseries_stats.len());
+        assert_eq!(trade_count, backtest_data.trades.len());
 
         // Cleanup
         let _ = client.delete_backtest(&id).await?;
@@ -308,6 +1327,41 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_backtest_bundle() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+        let path = "tests/test_backtest_bundle.tar.gz";
+
+        let response = client
+            .create_backtest_from_file("tests/data/test_data.backtest.json")
+            .await?;
+        let id: i32 = response.data.parse().unwrap();
+
+        // Test
+        client.export_backtest_bundle(&id, path, None).await?;
+        let response = client.import_backtest_bundle(path, None).await?;
+
+        // Validate
+        assert_eq!(response.status, "success");
+        let imported: i32 = response.data.parse().unwrap();
+        let backtest = client.get_backtest(&imported).await?;
+        assert_eq!(
+            backtest.data.unwrap().metadata.backtest_name,
+            "testing76543"
+        );
+
+        // Cleanup
+        let _ = client.delete_backtest(&id).await?;
+        let _ = client.delete_backtest(&imported).await?;
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     // #[ignore]
@@ -336,6 +1390,93 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_watch_live_stops_after_errors() {
+        let client = Trading::new("http://127.0.0.1:1");
+
+        // Test
+        let polled: Vec<Result<LiveEvent>> = client
+            .poll_live(1, Duration::from_millis(1), LiveDiff::new())
+            .collect()
+            .await;
+
+        // Validate
+        assert_eq!(polled.len(), WATCH_MAX_ERRORS);
+        assert!(polled.iter().all(|event| event.is_err()));
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_watch_live() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        let live_data: LiveData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let mut session = LiveSession::start(&client, &live_data.parameters).await?;
+        let mut events = client
+            .watch_live(session.id(), Duration::from_millis(100))
+            .await?;
+
+        // Test
+        session.push_trade(live_data.trades[0].clone());
+        let _ = session.flush().await?;
+        let event = events.next().await.expect("Stream ended")?;
+
+        // Validate
+        assert!(matches!(event, LiveEvent::Trade(_)));
+
+        // Cleanup
+        let _ = session.finalize().await?;
+        let _ = client.delete_live(&session.id()).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    // #[ignore]
+    async fn test_live_drift() -> Result<()> {
+        dotenv().ok();
+        let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
+        let client = Trading::new(&base_url);
+
+        // Pull test data
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        let live_data: LiveData =
+            serde_json::from_str(&mock_data).expect("JSON was not well-formatted");
+
+        let response = client.create_live(&live_data).await?;
+        let live_id =
+            get_id_from_string(&response.message).expect("Error getting id from message.");
+        let response = client
+            .create_backtest_from_file("tests/data/test_data.backtest.json")
+            .await?;
+        let backtest_id: i32 = response.data.parse().unwrap();
+
+        // Test
+        let report = client
+            .live_drift(&live_id, &backtest_id, &DriftConfig::default())
+            .await?;
+
+        // Validate
+        assert_eq!(report.matched_trades.len(), 1);
+        assert_eq!(report.matched_signals, 2);
+
+        // Cleanup
+        let _ = client.delete_live(&live_id).await?;
+        let _ = client.delete_backtest(&backtest_id).await?;
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     // #[ignore]
@@ -354,7 +1495,7 @@ mod tests {
         let id = get_id_from_string(&response.message).expect("Error getting id from message.");
 
         // Test
-        let response = client.list_live().await?;
+        let response = client.list_live(&ListQuery::new()).await?;
 
         // Validate
         assert_eq!(response.code, 200);
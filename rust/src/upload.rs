@@ -0,0 +1,403 @@
+use crate::error::{Error, Result};
+use mbinary::backtest::{BacktestData, BacktestMetaData, Encode, Signals, TimeseriesStats, Trades};
+use mbinary::backtest_encode::BacktestEncoder;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Default number of trades or signals encoded into a single upload chunk.
+pub const DEFAULT_CHUNK_ITEMS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Trades,
+    Signals,
+    Done,
+}
+
+/// `BacktestEncoder` output of a backtest split into chunks of at most `chunk_items` trades or
+/// signals. Concatenated, the chunks are byte for byte what `BacktestEncoder` produces.
+pub struct BacktestChunks {
+    backtest: BacktestData,
+    chunk_items: usize,
+    section: Section,
+    offset: usize,
+}
+
+impl BacktestChunks {
+    pub fn new(backtest: BacktestData, chunk_items: usize) -> Self {
+        BacktestChunks {
+            backtest,
+            chunk_items: chunk_items.max(1),
+            section: Section::Header,
+            offset: 0,
+        }
+    }
+}
+
+impl Iterator for BacktestChunks {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.section {
+                Section::Header => {
+                    encode_header(
+                        &self.backtest.metadata,
+                        &self.backtest.period_timeseries_stats,
+                        &self.backtest.daily_timeseries_stats,
+                        &mut bytes,
+                    );
+                    self.section = Section::Trades;
+                }
+                Section::Trades => {
+                    let trades = &self.backtest.trades;
+                    if !encode_items(trades, self.offset, self.chunk_items, &mut bytes) {
+                        self.section = Section::Signals;
+                        self.offset = 0;
+                        continue;
+                    }
+                    self.offset += self.chunk_items;
+                }
+                Section::Signals => {
+                    let signals = &self.backtest.signals;
+                    if !encode_items(signals, self.offset, self.chunk_items, &mut bytes) {
+                        self.section = Section::Done;
+                        continue;
+                    }
+                    self.offset += self.chunk_items;
+                }
+                Section::Done => return None,
+            }
+
+            return Some(bytes);
+        }
+    }
+}
+
+/// Stream-encodes a JSON `BacktestData` file into `sink` without loading the trades and
+/// signals into memory. The file is read three times: once for the metadata, timeseries and
+/// counts, then once each for the trades and the signals, encoded `chunk_items` at a time.
+pub fn encode_json_file<P, F>(path: P, chunk_items: usize, mut sink: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(Vec<u8>) -> Result<()>,
+{
+    let path = path.as_ref();
+    let chunk_items = chunk_items.max(1);
+
+    let header: JsonHeader = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    let mut bytes = Vec::new();
+    encode_header(
+        &header.metadata,
+        &header.period_timeseries_stats,
+        &header.daily_timeseries_stats,
+        &mut bytes,
+    );
+    sink(bytes)?;
+
+    stream_field::<Trades, _>(path, "trades", header.trades.0, chunk_items, &mut sink)?;
+    stream_field::<Signals, _>(path, "signals", header.signals.0, chunk_items, &mut sink)?;
+
+    Ok(())
+}
+
+/// `BacktestEncoder` output of the whole backtest, as sent by `Trading::create_backtest`.
+pub fn encode_backtest(backtest: &BacktestData) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = BacktestEncoder::new(&mut bytes);
+    encoder.encode_metadata(&backtest.metadata);
+    encoder.encode_timeseries(&backtest.period_timeseries_stats);
+    encoder.encode_timeseries(&backtest.daily_timeseries_stats);
+    encoder.encode_trades(&backtest.trades);
+    encoder.encode_signals(&backtest.signals);
+    bytes
+}
+
+fn encode_header(
+    metadata: &BacktestMetaData,
+    period_timeseries_stats: &Vec<TimeseriesStats>,
+    daily_timeseries_stats: &Vec<TimeseriesStats>,
+    bytes: &mut Vec<u8>,
+) {
+    let mut encoder = BacktestEncoder::new(bytes);
+    encoder.encode_metadata(metadata);
+    encoder.encode_timeseries(period_timeseries_stats);
+    encoder.encode_timeseries(daily_timeseries_stats);
+}
+
+/// Encodes `items[offset..offset + chunk_items]`, preceded by the length prefix of the whole
+/// vector on the first chunk. Returns false once past the end.
+fn encode_items<T: Encode>(
+    items: &[T],
+    offset: usize,
+    chunk_items: usize,
+    bytes: &mut Vec<u8>,
+) -> bool {
+    if offset == 0 {
+        bytes.extend((items.len() as u32).to_le_bytes());
+    } else if offset >= items.len() {
+        return false;
+    }
+
+    for item in items.iter().skip(offset).take(chunk_items) {
+        item.encode(bytes);
+    }
+    true
+}
+
+/// Encodes the `field` array of the JSON file in chunks, checking it still holds `expected`
+/// items.
+fn stream_field<T, F>(
+    path: &Path,
+    field: &'static str,
+    expected: usize,
+    chunk_items: usize,
+    sink: &mut F,
+) -> Result<()>
+where
+    T: Encode + for<'de> Deserialize<'de>,
+    F: FnMut(Vec<u8>) -> Result<()>,
+{
+    let mut bytes = (expected as u32).to_le_bytes().to_vec();
+    let mut pending = 0;
+    let mut failure: Option<Error> = None;
+
+    let mut on_item = |item: T| -> std::result::Result<(), String> {
+        item.encode(&mut bytes);
+        pending += 1;
+        if pending == chunk_items {
+            pending = 0;
+            if let Err(e) = sink(std::mem::take(&mut bytes)) {
+                let message = e.to_string();
+                failure = Some(e);
+                return Err(message);
+            }
+        }
+        Ok(())
+    };
+
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(File::open(path)?));
+    let count = deserializer.deserialize_map(FieldVisitor {
+        field,
+        seed: SeqSeed::new(&mut on_item),
+    });
+
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    let count = count?;
+    deserializer.end()?;
+
+    if count != expected {
+        return Err(Error::CustomError(format!(
+            "Backtest file changed while uploading, expected {} {} but read {}.",
+            expected, field, count
+        )));
+    }
+
+    if !bytes.is_empty() {
+        sink(bytes)?;
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct JsonHeader {
+    metadata: BacktestMetaData,
+    period_timeseries_stats: Vec<TimeseriesStats>,
+    daily_timeseries_stats: Vec<TimeseriesStats>,
+    trades: Count,
+    signals: Count,
+}
+
+/// Length of a JSON array, skipping over its items.
+struct Count(usize);
+
+impl<'de> Deserialize<'de> for Count {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut ignore = |_: IgnoredAny| Ok(());
+        SeqSeed::new(&mut ignore)
+            .deserialize(deserializer)
+            .map(Count)
+    }
+}
+
+/// Deserializes a sequence item by item into a callback, yielding the item count.
+struct SeqSeed<'a, T, F> {
+    on_item: &'a mut F,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T, F> SeqSeed<'a, T, F> {
+    fn new(on_item: &'a mut F) -> Self {
+        SeqSeed {
+            on_item,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, F> DeserializeSeed<'de> for SeqSeed<'_, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(T) -> std::result::Result<(), String>,
+{
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T, F> Visitor<'de> for SeqSeed<'_, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(T) -> std::result::Result<(), String>,
+{
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<usize, A::Error> {
+        let mut count = 0;
+        while let Some(item) = seq.next_element::<T>()? {
+            (self.on_item)(item).map_err(de::Error::custom)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Feeds one field of a JSON object to `seed`, skipping all others.
+struct FieldVisitor<'a, T, F> {
+    field: &'static str,
+    seed: SeqSeed<'a, T, F>,
+}
+
+impl<'de, T, F> Visitor<'de> for FieldVisitor<'_, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(T) -> std::result::Result<(), String>,
+{
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an object with a `{}` array", self.field)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<usize, A::Error> {
+        let mut seed = Some(self.seed);
+        let mut count = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match seed.take() {
+                Some(field_seed) if key == self.field => {
+                    count = Some(map.next_value_seed(field_seed)?);
+                }
+                other => {
+                    seed = other;
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        count.ok_or_else(|| de::Error::missing_field(self.field))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mbinary::backtest::StaticStats;
+    use std::fs;
+
+    fn load_backtest() -> BacktestData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.backtest.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    /// `StaticStats` is encoded as raw memory, so the padding after the trade counts holds
+    /// whatever the struct's memory did. Zeroes it so two encodings can be compared.
+    fn clear_padding(mut bytes: Vec<u8>, metadata: &BacktestMetaData) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        metadata.encode(&mut encoded);
+        let stats = encoded.len() - std::mem::size_of::<StaticStats>();
+        let start = stats
+            + std::mem::offset_of!(StaticStats, total_losing_trades)
+            + std::mem::size_of::<i32>();
+        let end = stats + std::mem::offset_of!(StaticStats, avg_profit);
+        bytes[start..end].fill(0);
+        bytes
+    }
+
+    #[test]
+    fn test_backtest_chunks_fixture() {
+        let backtest = load_backtest();
+        let metadata = backtest.metadata.clone();
+        let expected = clear_padding(encode_backtest(&backtest), &metadata);
+
+        // Test
+        let single: Vec<Vec<u8>> = BacktestChunks::new(backtest.clone(), 1).collect();
+        let whole: Vec<Vec<u8>> = BacktestChunks::new(backtest, DEFAULT_CHUNK_ITEMS).collect();
+
+        // Validate
+        assert_eq!(clear_padding(single.concat(), &metadata), expected);
+        assert_eq!(clear_padding(whole.concat(), &metadata), expected);
+    }
+
+    #[test]
+    fn test_backtest_chunks() {
+        let mut backtest = load_backtest();
+        let trade = backtest.trades[0].clone();
+        backtest.trades.extend(vec![trade; 4]);
+        let metadata = backtest.metadata.clone();
+        let expected = clear_padding(encode_backtest(&backtest), &metadata);
+
+        // Test
+        let chunks: Vec<Vec<u8>> = BacktestChunks::new(backtest, 2).collect();
+
+        // Validate
+        assert_eq!(chunks.len(), 1 + 3 + 1);
+        assert_eq!(clear_padding(chunks.concat(), &metadata), expected);
+    }
+
+    #[test]
+    fn test_encode_json_file() -> Result<()> {
+        let mut backtest = load_backtest();
+        let trade = backtest.trades[0].clone();
+        backtest.trades.extend(vec![trade; 2]);
+        let expected = clear_padding(encode_backtest(&backtest), &backtest.metadata);
+        let path = "tests/test_encode_json_file.json";
+        fs::write(path, serde_json::to_string(&backtest)?)?;
+
+        // Test
+        let mut chunks = Vec::new();
+        encode_json_file(path, 2, |bytes| {
+            chunks.push(bytes);
+            Ok(())
+        })?;
+
+        // Validate
+        assert_eq!(chunks.len(), 1 + 2 + 1);
+        assert_eq!(clear_padding(chunks.concat(), &backtest.metadata), expected);
+
+        // Cleanup
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::{Error, Result};
+use mbinary::symbols::Instrument;
+use mbinary::vendors::{VendorData, Vendors, YfinanceDataset};
+
+/// Instrument with its encoded `vendor_data` decoded.
+#[derive(Debug)]
+pub struct InstrumentView {
+    pub instrument: Instrument,
+    pub vendor_data: VendorData,
+}
+
+impl TryFrom<Instrument> for InstrumentView {
+    type Error = Error;
+
+    /// Fails when `vendor_data` doesn't decode to valid fields of the instrument's vendor.
+    fn try_from(instrument: Instrument) -> Result<Self> {
+        if !is_valid_vendor_data(instrument.vendor_data, &instrument.vendor) {
+            return Err(Error::CustomError(format!(
+                "Invalid vendor data {} for {:?} instrument {}.",
+                instrument.vendor_data, instrument.vendor, instrument.ticker
+            )));
+        }
+
+        let vendor_data = VendorData::decode(instrument.vendor_data, &instrument.vendor);
+        Ok(InstrumentView {
+            instrument,
+            vendor_data,
+        })
+    }
+}
+
+/// Checks each field `VendorData::decode` unpacks, which panics on unknown values.
+fn is_valid_vendor_data(raw: u64, vendor: &Vendors) -> bool {
+    match vendor {
+        Vendors::Internal => true,
+        Vendors::Databento => {
+            dbn::Dataset::try_from((raw & 0xFF) as u16).is_ok()
+                && dbn::SType::try_from(((raw >> 16) & 0xFF) as u8).is_ok()
+                && dbn::Schema::try_from(((raw >> 24) & 0xFF) as u16).is_ok()
+        }
+        Vendors::Yfinance => YfinanceDataset::try_from((raw & 0xFF) as u8).is_ok(),
+    }
+}
+
+impl InstrumentView {
+    /// Databento `(dataset, schema, stype)`, e.g. `("GLBX.MDP3", "mbp-1", "raw_symbol")`.
+    pub fn databento(&self) -> Option<(&str, &str, &str)> {
+        match &self.vendor_data {
+            VendorData::Databento(data) => Some((
+                data.dataset.as_str(),
+                data.schema.as_str(),
+                data.stype.as_str(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Filters on vendor-specific fields, unset fields match anything.
+#[derive(Debug, Clone, Default)]
+pub struct VendorFilter {
+    dataset: Option<String>,
+    schema: Option<String>,
+    stype: Option<String>,
+}
+
+impl VendorFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Vendor dataset, e.g. `GLBX.MDP3`.
+    pub fn dataset(mut self, dataset: &str) -> Self {
+        self.dataset = Some(dataset.to_string());
+        self
+    }
+
+    /// Vendor schema, e.g. `mbp-1`.
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    /// Vendor symbology type, e.g. `raw_symbol`.
+    pub fn stype(mut self, stype: &str) -> Self {
+        self.stype = Some(stype.to_string());
+        self
+    }
+
+    pub fn matches(&self, view: &InstrumentView) -> bool {
+        let is_match = |expected: &Option<String>, actual: &str| match expected {
+            Some(expected) => expected.eq_ignore_ascii_case(actual),
+            None => true,
+        };
+
+        match view.databento() {
+            Some((dataset, schema, stype)) => {
+                is_match(&self.dataset, dataset)
+                    && is_match(&self.schema, schema)
+                    && is_match(&self.stype, stype)
+            }
+            None => self.dataset.is_none() && self.schema.is_none() && self.stype.is_none(),
+        }
+    }
+}
+
+/// Renders the instruments as a plain text table.
+pub fn render_table(views: &[InstrumentView]) -> String {
+    let header = [
+        "id",
+        "ticker",
+        "name",
+        "dataset",
+        "vendor",
+        "vendor_dataset",
+        "schema",
+        "stype",
+        "active",
+    ];
+
+    let rows: Vec<Vec<String>> = views
+        .iter()
+        .map(|view| {
+            let instrument = &view.instrument;
+            let (vendor_dataset, schema, stype) = view.databento().unwrap_or(("", "", ""));
+
+            vec![
+                instrument
+                    .instrument_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                instrument.ticker.clone(),
+                instrument.name.clone(),
+                format!("{:?}", instrument.dataset),
+                format!("{:?}", instrument.vendor),
+                vendor_dataset.to_string(),
+                schema.to_string(),
+                stype.to_string(),
+                instrument.active.to_string(),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain(std::iter::once(title.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let mut table = format_row(&header, &widths);
+    table.push('\n');
+    table.push_str(&format_row(&as_strs(&separator), &widths));
+    table.push('\n');
+
+    for row in rows.iter() {
+        table.push_str(&format_row(&as_strs(row), &widths));
+        table.push('\n');
+    }
+
+    table
+}
+
+fn as_strs(cells: &[String]) -> Vec<&str> {
+    cells.iter().map(|cell| cell.as_str()).collect()
+}
+
+fn format_row(cells: &[&str], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths.iter())
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect::<Vec<String>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mbinary::enums::Dataset;
+    use mbinary::vendors::{DatabentoData, Vendors};
+    use std::str::FromStr;
+
+    fn dummy_instrument(ticker: &str, schema: &str) -> anyhow::Result<Instrument> {
+        let vendor_data = VendorData::Databento(DatabentoData {
+            schema: dbn::Schema::from_str(schema)?,
+            dataset: dbn::Dataset::from_str("GLBX.MDP3")?,
+            stype: dbn::SType::from_str("raw_symbol")?,
+        });
+
+        Ok(Instrument::new(
+            Some(1),
+            ticker,
+            "Lean hogs",
+            Dataset::Futures,
+            Vendors::Databento,
+            vendor_data.encode(),
+            1,
+            1,
+            1,
+            false,
+            true,
+        ))
+    }
+
+    #[test]
+    fn test_instrument_view() -> anyhow::Result<()> {
+        let instrument = dummy_instrument("HEG4", "mbp-1")?;
+
+        // Test
+        let view = InstrumentView::try_from(instrument)?;
+
+        // Validate
+        assert_eq!(view.databento(), Some(("GLBX.MDP3", "mbp-1", "raw_symbol")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_vendor_data() -> anyhow::Result<()> {
+        let mut instrument = dummy_instrument("HEG4", "mbp-1")?;
+        instrument.vendor_data = u64::MAX;
+
+        // Test
+        let view = InstrumentView::try_from(instrument);
+
+        // Validate
+        assert!(matches!(view, Err(Error::CustomError(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vendor_filter() -> anyhow::Result<()> {
+        let mbp = InstrumentView::try_from(dummy_instrument("HEG4", "mbp-1")?)?;
+        let ohlcv = InstrumentView::try_from(dummy_instrument("HEJ4", "ohlcv-1h")?)?;
+        let filter = VendorFilter::new().dataset("GLBX.MDP3").schema("mbp-1");
+
+        // Validate
+        assert!(filter.matches(&mbp));
+        assert!(!filter.matches(&ohlcv));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_table() -> anyhow::Result<()> {
+        let views = vec![InstrumentView::try_from(dummy_instrument(
+            "HEG4", "mbp-1",
+        )?)?];
+
+        // Test
+        let table = render_table(&views);
+        let lines: Vec<&str> = table.lines().collect();
+
+        // Validate
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id  ticker  name"));
+        assert!(lines[2].contains("mbp-1   raw_symbol  true"));
+
+        Ok(())
+    }
+}
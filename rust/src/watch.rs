@@ -0,0 +1,89 @@
+use crate::error::Result;
+use mbinary::backtest::{Signals, Trades};
+use mbinary::live::{AccountSummary, LiveData};
+
+/// Change observed on a live session.
+#[derive(Debug, Clone)]
+pub enum LiveEvent {
+    Trade(Trades),
+    Signal(Signals),
+    Account(AccountSummary),
+}
+
+/// What has already been seen of a live session, used to turn full snapshots into events.
+/// Trades and signals are append only on the server, so they are tracked by count and
+/// identical repeated fills are still reported.
+#[derive(Debug, Default)]
+pub struct LiveDiff {
+    trades: usize,
+    signals: usize,
+    account: Option<String>,
+}
+
+impl LiveDiff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the trades, signals and account snapshot of `data` not seen before, in order.
+    pub fn update(&mut self, data: &LiveData) -> Result<Vec<LiveEvent>> {
+        let mut events = Vec::new();
+
+        for trade in data.trades.iter().skip(self.trades) {
+            events.push(LiveEvent::Trade(trade.clone()));
+        }
+        self.trades = self.trades.max(data.trades.len());
+
+        for signal in data.signals.iter().skip(self.signals) {
+            events.push(LiveEvent::Signal(signal.clone()));
+        }
+        self.signals = self.signals.max(data.signals.len());
+
+        let account = serde_json::to_string(&data.account)?;
+        if self.account.as_ref() != Some(&account) {
+            self.account = Some(account);
+            events.push(LiveEvent::Account(data.account.clone()));
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load_live() -> LiveData {
+        let mock_data =
+            fs::read_to_string("tests/data/test_data.live.json").expect("Unable to read file");
+        serde_json::from_str(&mock_data).expect("JSON was not well-formatted")
+    }
+
+    #[test]
+    fn test_live_diff() -> Result<()> {
+        let mut live_data = load_live();
+        let mut diff = LiveDiff::new();
+        let initial = diff.update(&live_data)?;
+
+        let mut trade = live_data.trades[0].clone();
+        trade.trade_id = 2;
+        live_data.trades.push(trade.clone());
+
+        // Test
+        let events = diff.update(&live_data)?;
+        let unchanged = diff.update(&live_data)?;
+        live_data.trades.push(trade);
+        let repeated = diff.update(&live_data)?;
+
+        // Validate
+        assert_eq!(initial.len(), 3);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], LiveEvent::Trade(trade) if trade.trade_id == 2));
+        assert!(unchanged.is_empty());
+        assert_eq!(repeated.len(), 1);
+        assert!(matches!(&repeated[0], LiveEvent::Trade(trade) if trade.trade_id == 2));
+
+        Ok(())
+    }
+}
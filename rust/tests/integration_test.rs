@@ -11,7 +11,7 @@ use mbinary::vendors::{DatabentoData, VendorData};
 use midas_client::historical::Historical;
 use midas_client::instrument::Instruments;
 use serial_test::serial;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 // use mbinary::params::RetrieveParams;
 
@@ -54,7 +54,7 @@ async fn delete_dummy_instrument(id: &i32) -> anyhow::Result<()> {
     dotenv().ok();
     let base_url = std::env::var("MIDAS_URL").expect("Expected database_url.");
     let client = Instruments::new(&base_url);
-    let _ = client.delete_symbol(&id).await?;
+    let _ = client.delete_symbol(id).await?;
 
     Ok(())
 }
@@ -62,7 +62,7 @@ async fn delete_dummy_instrument(id: &i32) -> anyhow::Result<()> {
 async fn create_dummy_records_file(
     ticker: &str,
     dataset: Dataset,
-    filename: &PathBuf,
+    filename: &Path,
 ) -> anyhow::Result<i32> {
     dotenv().ok();
     let id = create_dummy_instrument(ticker, dataset).await?;
@@ -129,7 +129,7 @@ async fn create_dummy_records_file(
         .expect("Encoding failed");
 
     // Create records file
-    let _ = encoder.write_to_file(filename, false)?;
+    encoder.write_to_file(filename, false)?;
 
     Ok(id)
 }
@@ -156,7 +156,7 @@ async fn test_create_mbp_from_file() -> anyhow::Result<()> {
     assert_eq!(result.status, "success");
 
     // Cleanup
-    let _ = delete_dummy_instrument(&id).await?;
+    delete_dummy_instrument(&id).await?;
 
     let _ = tokio::fs::remove_file(path).await;
 
@@ -220,7 +220,7 @@ async fn test_create_mbp_from_file_duplicate_error() -> anyhow::Result<()> {
         .expect("Encoding failed");
 
     // Create records file
-    let _ = encoder.write_to_file(&path, false)?;
+    encoder.write_to_file(&path, false)?;
 
     // Test
     let result = client.create_mbp_from_file(filename).await?;
@@ -229,7 +229,7 @@ async fn test_create_mbp_from_file_duplicate_error() -> anyhow::Result<()> {
     assert_eq!(result.status, "failed");
 
     // Cleanup
-    let _ = delete_dummy_instrument(&id).await?;
+    delete_dummy_instrument(&id).await?;
     let _ = tokio::fs::remove_file(path).await;
 
     Ok(())